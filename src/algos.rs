@@ -1,6 +1,9 @@
+use crate::{
+    merror, ConstMedians, Me, QuantileMethod, RankCheck, RankRange, SelectStats, SelectStrategy,
+};
+use alloc::{format, vec::Vec};
 use core::cmp::{Ordering, Ordering::*};
-use indxvec::{Mutops, Vecops};
-use std::ops::Range;
+use core::ops::{BitAnd, BitOr, BitXor, Range};
 
 /// Mask of the first bit of a u64
 pub const FIRST_BIT: u64 = 0x80_00_00_00_00_00_00_00;
@@ -9,7 +12,7 @@ pub const FIRST_BIT: u64 = 0x80_00_00_00_00_00_00_00;
 /// Copies &[u64] to Vec<u8>
 pub fn tobebytes(v: &[u64]) -> Vec<u8> {
     let n = v.len();
-    let mut bytes = vec![0u8;8*n];
+    let mut bytes = alloc::vec![0u8;8*n];
     for i in 0..n {
         bytes[8*i..][..8].copy_from_slice(&v[i].to_be_bytes());
     }
@@ -40,29 +43,6 @@ pub fn midof3<T>(
     indx2
 }
 
-/// index of middle valued ref of three, using at most three comparisons {
-fn midof3_refs<T>(
-    s: &[&T],
-    indx0: usize,
-    indx1: usize,
-    indx2: usize,
-    c: &mut impl FnMut(&T, &T) -> Ordering,
-) -> usize {
-    let (min, max) = if c(s[indx0], s[indx1]) == Less {
-        (indx0, indx1)
-    } else {
-        (indx1, indx0)
-    };
-    let lastref = s[indx2];
-    if c(s[min], lastref) != Less {
-        return min;
-    };
-    if c(lastref, s[max]) != Less {
-        return max;
-    };
-    indx2
-}
-
 /// Scan a slice of f64s for NANs
 pub fn nans(v: &[f64]) -> bool {
     for &f in v {
@@ -73,34 +53,31 @@ pub fn nans(v: &[f64]) -> bool {
     false
 }
 
-/// kth item from rng (ascending or descending, depending on `c`)
-/// using binary insert k-sort
+/// kth item from rng (ascending or descending, depending on `c`),
+/// by selection among the references to the items of `rng`
 pub fn best_k<T, F>(s: &[T], k: usize, rng: Range<usize>, c: F) -> &T
 where
     F: Fn(&T, &T) -> Ordering,
 {
     let n = rng.len();
     assert!((k > 0) & (k <= n));
-    let mut k_sorted: Vec<&T> = s.iter().skip(rng.start).take(k).collect();
-    k_sorted.sort_unstable_by(|&a, &b| c(a, b));
-    let mut k_max = k_sorted[k - 1];
-    for si in s.iter() {
-        if c(si, k_max) == Less {
-            let insert_pos = match k_sorted.binary_search_by(|j| c(j, si)) {
-                Ok(ins) => ins + 1,
-                Err(ins) => ins,
-            };
-            k_sorted.insert(insert_pos, si);
-            k_sorted.pop();
-            k_max = k_sorted[k - 1];
-        };
-    }
-    k_max
+    let mut refs: Vec<&T> = s[rng].iter().collect();
+    select_mut(
+        &mut refs,
+        k - 1,
+        SelectStrategy::MidOf3,
+        &mut |a: &&T, b: &&T| c(a, b),
+    );
+    refs[k - 1]
 }
 
 /// Ref to the minimum item within a range in a slice,  
 /// or to the maximum, when invoked with swapped arguments of comparator c: `|a,b| c(b,a)`
-pub fn extremum<'a, T>(s: &'a[T], rng: Range<usize>, c: &mut impl FnMut(&T, &T) -> Ordering) -> &'a T {
+pub fn extremum<'a, T>(
+    s: &'a [T],
+    rng: Range<usize>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> &'a T {
     let mut min = &s[rng.start];
     for si in s.iter().take(rng.end).skip(rng.start + 1) {
         if c(si, min) == Ordering::Less {
@@ -112,28 +89,36 @@ pub fn extremum<'a, T>(s: &'a[T], rng: Range<usize>, c: &mut impl FnMut(&T, &T)
 /// Refs to the smallest two values within a range in a slice    
 /// or to the largest two, when invoked with swapped arguments of comparator c: `|a,b| c(b,a)`.  
 /// The first returned item always refers to the extremum.
-pub fn best_two<'a, T>(s: &'a[T], rng: Range<usize>, c: &mut impl FnMut(&T, &T) -> Ordering) -> (&'a T, &'a T) {
-    let (mut m1, mut m2) = if c(&s[rng.start+1], &s[rng.start]) == Ordering::Less {
-        (&s[rng.start+1], &s[rng.start])
+pub fn best_two<'a, T>(
+    s: &'a [T],
+    rng: Range<usize>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> (&'a T, &'a T) {
+    let (mut m1, mut m2) = if c(&s[rng.start + 1], &s[rng.start]) == Ordering::Less {
+        (&s[rng.start + 1], &s[rng.start])
     } else {
-        (&s[rng.start], &s[rng.start+1])
+        (&s[rng.start], &s[rng.start + 1])
     };
     for si in s.iter().take(rng.end).skip(rng.start + 2) {
-        if c(si,m2) == Ordering::Less {
-            if c(si,m1) == Ordering::Less {
+        if c(si, m2) == Ordering::Less {
+            if c(si, m1) == Ordering::Less {
                 m2 = m1;
                 m1 = si;
             } else {
                 m2 = si;
             };
         };
-    };
+    }
     (m1, m2)
 }
 
 /// Ref to the minimum item within a range in a slice of refs  
 /// or to the maximum, when invoked with swapped arguments of comparator c: `|a,b| c(b,a)`
-pub fn extremum_refs<'a, T>(s: &[&'a T], rng: Range<usize>, c: &mut impl FnMut(&T, &T) -> Ordering) -> &'a T {
+pub fn extremum_refs<'a, T>(
+    s: &[&'a T],
+    rng: Range<usize>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> &'a T {
     let mut m = s[rng.start];
     for si in s.iter().take(rng.end).skip(rng.start + 1) {
         if c(si, m) == Ordering::Less {
@@ -148,28 +133,32 @@ pub fn extremum_refs<'a, T>(s: &[&'a T], rng: Range<usize>, c: &mut impl FnMut(&
 pub fn best_two_refs<'a, T>(
     s: &[&'a T],
     rng: Range<usize>,
-    c: &mut impl FnMut(&T, &T) -> Ordering
+    c: &mut impl FnMut(&T, &T) -> Ordering,
 ) -> (&'a T, &'a T) {
-    let (mut m1, mut m2) = if c(s[rng.start+1], s[rng.start]) == Ordering::Less {
-        (s[rng.start+1], s[rng.start])
+    let (mut m1, mut m2) = if c(s[rng.start + 1], s[rng.start]) == Ordering::Less {
+        (s[rng.start + 1], s[rng.start])
     } else {
-        (s[rng.start], s[rng.start+1])
+        (s[rng.start], s[rng.start + 1])
     };
     for si in s.iter().take(rng.end).skip(rng.start + 2) {
-        if c(si,m2) == Ordering::Less {
-            if c(si,m1) == Ordering::Less {
+        if c(si, m2) == Ordering::Less {
+            if c(si, m1) == Ordering::Less {
                 m2 = m1;
                 m1 = si;
             } else {
                 m2 = si;
             };
         };
-    };
+    }
     (m1, m2)
 }
 
 /// measure errors from centre (for testing)
 /// requires quantising to f64 for accuracy
+#[deprecated(
+    since = "3.1.0",
+    note = "use `is_median_by`, `is_kth_by` or `rank_range_of`"
+)]
 pub fn qbalance<T>(s: &[T], centre: &f64, q: impl Fn(&T) -> f64) -> i64 {
     let mut bal = 0_i64;
     let mut eq = 0_i64;
@@ -189,6 +178,129 @@ pub fn qbalance<T>(s: &[T], centre: &f64, q: impl Fn(&T) -> f64) -> i64 {
     1
 }
 
+/// Counts of the items of `s` less than, equal to and greater than `x`, by comparator `c`.
+/// Uses exactly `s.len()` comparisons.
+pub fn rank_range_of<T>(s: &[T], x: &T, c: &mut impl FnMut(&T, &T) -> Ordering) -> RankRange {
+    let mut counts = RankRange::default();
+    for si in s {
+        match c(si, x) {
+            Less => counts.less += 1,
+            Equal => counts.equal += 1,
+            Greater => counts.greater += 1,
+        };
+    }
+    counts
+}
+
+/// Checks that `m` is a median of `s` by comparator `c`, in `s.len()` comparisons.
+/// For even lengths, either of the two middle items is accepted.
+/// Returns `size` error for empty `s`.
+pub fn is_median_by<T>(
+    s: &[T],
+    m: &T,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> Result<RankCheck, Me> {
+    let n = s.len();
+    if n == 0 {
+        return merror("size", "is_median_by: zero length data");
+    };
+    Ok(RankCheck {
+        counts: rank_range_of(s, m, c),
+        claimed: (n - 1) / 2..n / 2 + 1,
+    })
+}
+
+/// Checks that `x` is the item of rank `k` (0-based) of `s` by comparator `c`, in `s.len()` comparisons.
+/// Returns `size` error when `k` is out of range.
+pub fn is_kth_by<T>(
+    s: &[T],
+    k: usize,
+    x: &T,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> Result<RankCheck, Me> {
+    let n = s.len();
+    if k >= n {
+        return merror("size", format!("is_kth_by: rank {k} out of range 0..{n}"));
+    };
+    Ok(RankCheck {
+        counts: rank_range_of(s, x, c),
+        claimed: k..k + 1,
+    })
+}
+
+/// Floor of `x`, which is not available in `core`
+fn floor(x: f64) -> f64 {
+    let t = x as i64 as f64;
+    if t > x {
+        t - 1.0
+    } else {
+        t
+    }
+}
+
+/// Ascending sorted and deduplicated copy of ranks `ks`, checked to be within `0..n`
+pub(super) fn sorted_ranks(ks: &[usize], n: usize) -> Result<Vec<usize>, Me> {
+    if let Some(&k) = ks.iter().find(|&&k| k >= n) {
+        return merror("size", format!("rank {k} out of range 0..{n}"));
+    };
+    let mut sorted = ks.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    Ok(sorted)
+}
+
+/// 0-based ranks `(lo,hi)` of the two order statistics to interpolate between
+/// and the weight of the `hi` one, for quantile `p` of `n` items, as defined by `method`.
+/// The quantile is then `(1-weight)*x[lo] + weight*x[hi]`.
+pub fn quantile_ranks(p: f64, n: usize, method: QuantileMethod) -> (usize, usize, f64) {
+    use QuantileMethod::*;
+    const FUZZ: f64 = 4.0 * f64::EPSILON; // as in R, guards against rounding errors in n*p
+    let nf = n as f64;
+    let m = match method {
+        InvertedCdf | AveragedInvertedCdf | InterpolatedInvertedCdf => 0.0,
+        ClosestObservation => -0.5,
+        Hazen => 0.5,
+        Weibull => p,
+        Linear => 1.0 - p,
+        MedianUnbiased => (p + 1.0) / 3.0,
+        NormalUnbiased => p / 4.0 + 3.0 / 8.0,
+    };
+    let h = nf * p + m; // 1-based real valued position
+    let j = floor(h + FUZZ);
+    let mut g = h - j;
+    if g.abs() < FUZZ {
+        g = 0.0;
+    };
+    let weight = match method {
+        InvertedCdf => {
+            if g > 0.0 {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        AveragedInvertedCdf => {
+            if g > 0.0 {
+                1.0
+            } else {
+                0.5
+            }
+        }
+        ClosestObservation => {
+            if g == 0.0 && (j as i64) % 2 == 0 {
+                0.0
+            } else {
+                1.0
+            }
+        }
+        _ => g,
+    };
+    // convert 1-based order statistics j and j+1 to clamped 0-based ranks
+    let lo = (j - 1.0).clamp(0.0, nf - 1.0) as usize;
+    let hi = j.clamp(0.0, nf - 1.0) as usize;
+    (lo, hi, weight)
+}
+
 /// Odd median of `&u[8]`
 pub fn oddmedianu8(s: &[u8]) -> u8 {
     let need = s.len() / 2; // median target position
@@ -211,7 +323,7 @@ pub fn oddmedianu8(s: &[u8]) -> u8 {
 }
 
 /// Even medians of `&[u8]`
-pub fn evenmedianu8(s: &[u8]) -> (u8,u8) {
+pub fn evenmedianu8(s: &[u8]) -> (u8, u8) {
     let need = s.len() / 2; // first median target position
     let mut histogram = [0_usize; 256];
     let mut cummulator = 0_usize;
@@ -237,7 +349,7 @@ pub fn evenmedianu8(s: &[u8]) -> (u8,u8) {
             }; // while cummulator < need, loop also continues
         } else {
             // the second item is in the first following non-zero bucket
-            return (res1,i);
+            return (res1, i);
         }; // found the second
     }
     if firstres {
@@ -247,96 +359,400 @@ pub fn evenmedianu8(s: &[u8]) -> (u8,u8) {
     }
 }
 
-/// Median of odd sized u64 data
-pub fn oddmedianu64(s: &mut [u64]) -> &u64 {
+/// Unsigned integer keys, which can be partitioned by their individual bits
+pub trait KeyBits:
+    Copy + Ord + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self>
+{
+    /// All bits clear
+    const ZERO: Self;
+    /// All bits set
+    const ONES: Self;
+    /// Mask of the highest set bit, `self` must be non zero
+    fn highest_bit(self) -> Self;
+}
+
+macro_rules! impl_keybits {
+    ($($t:ty),*) => {$(
+        impl KeyBits for $t {
+            const ZERO: Self = 0;
+            const ONES: Self = <$t>::MAX;
+            fn highest_bit(self) -> Self {
+                1 << self.ilog2()
+            }
+        }
+    )*};
+}
+impl_keybits!(u8, u16, u32, u64, u128, usize);
+
+/// Mask of the bits that differ between the items of unsigned keys data
+pub fn diffbits<K: KeyBits>(s: &[K]) -> K {
+    let (mut or, mut and) = (K::ZERO, K::ONES);
+    for &x in s {
+        or = or | x;
+        and = and & x;
+    }
+    or ^ and
+}
+
+/// Length of the blocks of `block_part`, small enough for their offsets to fit in u8
+const BLOCK: usize = 64;
+
+/// Branch reduced two way partitioning of `rng`, in the style of BlockQuicksort
+/// (Edelkamp and Weiss, 2016). Moves the items satisfying `pred` to the front
+/// and returns the subscript of the first item that does not.
+/// The outcomes of `pred` are recorded, without branching, as offsets into one block
+/// from each end, which are then swapped in a batch. Intended for primitive types,
+/// where the mispredicted branches of the usual partitioning dominate its cost.
+pub fn block_part<T>(s: &mut [T], rng: Range<usize>, pred: impl FnMut(&T) -> bool) -> usize {
+    block_part_counted(s, rng, pred).0
+}
+
+/// `block_part`, also returning the number of swaps made
+fn block_part_counted<T>(
+    s: &mut [T],
+    rng: Range<usize>,
+    mut pred: impl FnMut(&T) -> bool,
+) -> (usize, usize) {
+    let (mut l, mut r) = (rng.start, rng.end);
+    let mut swaps = 0_usize;
+    let (mut offl, mut offr) = ([0_u8; BLOCK], [0_u8; BLOCK]);
+    let (mut numl, mut numr, mut startl, mut startr) = (0, 0, 0, 0);
+    while r - l > 2 * BLOCK {
+        if numl == 0 {
+            // left block items that do not belong to the front
+            startl = 0;
+            for i in 0..BLOCK {
+                offl[numl] = i as u8;
+                numl += usize::from(!pred(&s[l + i]));
+            }
+        };
+        if numr == 0 {
+            // right block items that belong to the front
+            startr = 0;
+            for i in 0..BLOCK {
+                offr[numr] = i as u8;
+                numr += usize::from(pred(&s[r - 1 - i]));
+            }
+        };
+        let num = numl.min(numr);
+        swaps += num;
+        for j in 0..num {
+            s.swap(
+                l + offl[startl + j] as usize,
+                r - 1 - offr[startr + j] as usize,
+            );
+        }
+        (numl, numr, startl, startr) = (numl - num, numr - num, startl + num, startr + num);
+        if numl == 0 {
+            l += BLOCK;
+        };
+        if numr == 0 {
+            r -= BLOCK;
+        };
+    }
+    // the remainder, including any partly processed blocks
+    let mut front = l;
+    for i in l..r {
+        if pred(&s[i]) {
+            if front != i {
+                s.swap(front, i);
+                swaps += 1;
+            };
+            front += 1;
+        };
+    }
+    (front, swaps)
+}
+
+/// Partitions `rng` of unsigned keys data by `bitval`, like `part_binary`, returning the start of the gt partition.
+/// Also returns the masks of the bits that differ within the lt and within the gt partitions,
+/// so that the bits which would not split them further can be skipped.
+/// Uses the branch reduced `block_part`.
+pub fn part_bits<K: KeyBits>(s: &mut [K], rng: &Range<usize>, bitval: K) -> (usize, K, K) {
+    let (gtsub, ltdiff, gtdiff, _) = part_bits_counted(s, rng, bitval);
+    (gtsub, ltdiff, gtdiff)
+}
+
+/// `part_bits`, also returning the number of swaps made
+fn part_bits_counted<K: KeyBits>(
+    s: &mut [K],
+    rng: &Range<usize>,
+    bitval: K,
+) -> (usize, K, K, usize) {
+    let (gtsub, swaps) = block_part_counted(s, rng.clone(), |&x| (x & bitval) == K::ZERO);
+    (
+        gtsub,
+        diffbits(&s[rng.start..gtsub]),
+        diffbits(&s[gtsub..rng.end]),
+        swaps,
+    )
+}
+
+/// Adds the `comparisons` and `swaps` of one step of a selection to the stats `st`, when present.
+/// A partition pass also records the length of the range `remaining` in play after it.
+fn record(
+    st: &mut Option<&mut SelectStats>,
+    comparisons: usize,
+    swaps: usize,
+    remaining: Option<usize>,
+) {
+    if let Some(st) = st {
+        st.comparisons += comparisons;
+        st.swaps += swaps;
+        if let Some(len) = remaining {
+            st.passes += 1;
+            st.remaining.push(len);
+        };
+    };
+}
+
+/// Comparator `c`, counting its calls in `count`
+fn counting<'c, T>(
+    c: &'c mut impl FnMut(&T, &T) -> Ordering,
+    count: &'c mut usize,
+) -> impl FnMut(&T, &T) -> Ordering + 'c {
+    move |a, b| {
+        *count += 1;
+        c(a, b)
+    }
+}
+
+/// Median of odd sized unsigned keys data, by binary partitioning.  
+/// Partitions only by the bits that differ within the current range, highest first,
+/// so bunched data converges in as few passes as well spread data.
+pub fn oddmedian_bits<K: KeyBits>(s: &mut [K]) -> &K {
+    oddmedian_bits_recorded(s, &mut None)
+}
+
+/// `oddmedian_bits`, adding its work to the stats `st`, when present
+pub(super) fn oddmedian_bits_recorded<'a, K: KeyBits>(
+    s: &'a mut [K],
+    st: &mut Option<&mut SelectStats>,
+) -> &'a K {
     let mut rng = 0..s.len();
     let need = s.len() / 2; // median target position in fully partitioned
-    let mut bitval = FIRST_BIT; // set the most significant bit
+    let mut diff = diffbits(s); // bits that differ within rng
+    let mut cmps = 0_usize; // comparisons of the endgame
     loop {
-        let gtsub = s.part_binary(&rng, bitval);
-        if bitval == 1 {
+        if diff == K::ZERO {
             // termination of bit iterations: same values left
-            if need < gtsub {
-                return &s[gtsub - 1];
-            };
-            return &s[gtsub];
+            return &s[need];
         };
+        let bitval = diff.highest_bit();
+        let (gtsub, ltdiff, gtdiff, swaps) = part_bits_counted(s, &rng, bitval);
         // well inside lt partition, iterate on it
         if need + 2 < gtsub {
+            record(st, rng.len(), swaps, Some(gtsub - rng.start));
             rng.end = gtsub;
-            bitval >>= 1; // next bit
+            diff = ltdiff;
             continue;
         };
         // well inside gt partition, iterate on it
         if need > gtsub + 1 {
+            record(st, rng.len(), swaps, Some(rng.end - gtsub));
             rng.start = gtsub;
-            bitval >>= 1; // next bit
+            diff = gtdiff;
             continue;
         };
-        // penultimate place in lt partition, find the second maximum
-        if need + 2 == gtsub {
-            return best_two(s, rng.start..gtsub,&mut |a,b| b.cmp(a)).1;
-        };
-        // last place in the lt partition, find its maximum
-        if need + 1 == gtsub {
-            return extremum(s, rng.start..gtsub,&mut |a,b| b.cmp(a));
-        };
-        // first place in gt partition, find its minimum
-        if need == gtsub {
-            return extremum(s, gtsub..rng.end, &mut |a,b| a.cmp(b));
+        record(st, rng.len(), swaps, Some(0));
+        let res = if need + 2 == gtsub {
+            // penultimate place in lt partition, find the second maximum
+            best_two(
+                s,
+                rng.start..gtsub,
+                &mut counting(&mut |a: &K, b: &K| b.cmp(a), &mut cmps),
+            )
+            .1
+        } else if need + 1 == gtsub {
+            // last place in the lt partition, find its maximum
+            extremum(
+                s,
+                rng.start..gtsub,
+                &mut counting(&mut |a: &K, b: &K| b.cmp(a), &mut cmps),
+            )
+        } else if need == gtsub {
+            // first place in gt partition, find its minimum
+            extremum(
+                s,
+                gtsub..rng.end,
+                &mut counting(&mut |a: &K, b: &K| a.cmp(b), &mut cmps),
+            )
+        } else {
+            // second place in gt partition, find its second minimum
+            best_two(
+                s,
+                gtsub..rng.end,
+                &mut counting(&mut |a: &K, b: &K| a.cmp(b), &mut cmps),
+            )
+            .1
         };
-        // second place in gt partition, find its second minimum
-        return best_two(s, gtsub..rng.end, &mut |a,b| a.cmp(b)).1;
+        record(st, cmps, 0, None);
+        return res;
     }
 }
 
-/// Median of even sized u64 data
-pub fn evenmedianu64(s: &mut [u64]) -> (&u64, &u64) {
+/// Medians of even sized unsigned keys data, by binary partitioning.
+/// Partitions only by the bits that differ within the current range, as `oddmedian_bits`.
+pub fn evenmedian_bits<K: KeyBits>(s: &mut [K]) -> (&K, &K) {
+    evenmedian_bits_recorded(s, &mut None)
+}
+
+/// `evenmedian_bits`, adding its work to the stats `st`, when present
+pub(super) fn evenmedian_bits_recorded<'a, K: KeyBits>(
+    s: &'a mut [K],
+    st: &mut Option<&mut SelectStats>,
+) -> (&'a K, &'a K) {
     let mut rng = 0..s.len();
     let need = s.len() / 2 - 1; // first median target position
-    let mut bitval = FIRST_BIT; // set the most significant bit
+    let mut diff = diffbits(s); // bits that differ within rng
+    let mut cmps = 0_usize; // comparisons of the endgame
     loop {
-        let gtsub = s.part_binary(&rng, bitval);
-        if bitval == 1 {
+        if diff == K::ZERO {
             // termination of bit iterations: same values left
-            if need + 1 < gtsub {
-                return (&s[gtsub - 2], &s[gtsub - 1]);
-            };
-            if need + 1 == gtsub {
-                return (&s[gtsub - 1], &s[gtsub]);
-            };
-            return (&s[gtsub], &s[gtsub + 1]);
+            return (&s[need], &s[need + 1]);
         };
+        let bitval = diff.highest_bit();
+        let (gtsub, ltdiff, gtdiff, swaps) = part_bits_counted(s, &rng, bitval);
         // well inside lt partition, iterate on it
         if need + 2 < gtsub {
+            record(st, rng.len(), swaps, Some(gtsub - rng.start));
             rng.end = gtsub;
-            bitval >>= 1; // next bit
+            diff = ltdiff;
             continue;
         };
         // well inside gt partition, iterate on it
         if need > gtsub {
+            record(st, rng.len(), swaps, Some(rng.end - gtsub));
             rng.start = gtsub;
-            bitval >>= 1; // next bit
+            diff = gtdiff;
             continue;
         };
-        // penultimate place in lt partition, solution is the maxima pair:
-        if need + 2 == gtsub {
-            let (m1,m2) = best_two(s, rng.start..gtsub,&mut |a,b| b.cmp(a));
-            return (m2,m1)
-        };
-        // last place in the lt partition, return max of lt and min of gt partitions
-        if need + 1 == gtsub {
-            return (extremum(s, rng.start..gtsub,&mut |a,b| b.cmp(a)),
-                    extremum(s, gtsub..rng.end,&mut |a,b| a.cmp(b)));
-        };
-        // first place in gt partition, the solution is its minima pair:
-        if need == gtsub {
-            return best_two(s, gtsub..rng.end,&mut |a,b| a.cmp(b));
+        record(st, rng.len(), swaps, Some(0));
+        let res = if need + 2 == gtsub {
+            // penultimate place in lt partition, solution is the maxima pair:
+            let (m1, m2) = best_two(
+                s,
+                rng.start..gtsub,
+                &mut counting(&mut |a: &K, b: &K| b.cmp(a), &mut cmps),
+            );
+            (m2, m1)
+        } else if need + 1 == gtsub {
+            // last place in the lt partition, return max of lt and min of gt partitions
+            let m1 = extremum(
+                s,
+                rng.start..gtsub,
+                &mut counting(&mut |a: &K, b: &K| b.cmp(a), &mut cmps),
+            );
+            let m2 = extremum(
+                s,
+                gtsub..rng.end,
+                &mut counting(&mut |a: &K, b: &K| a.cmp(b), &mut cmps),
+            );
+            (m1, m2)
+        } else {
+            // first place in gt partition, the solution is its minima pair:
+            best_two(
+                s,
+                gtsub..rng.end,
+                &mut counting(&mut |a: &K, b: &K| a.cmp(b), &mut cmps),
+            )
         };
+        record(st, cmps, 0, None);
+        return res;
+    }
+}
+
+/// Median of odd sized u64 data
+pub fn oddmedianu64(s: &mut [u64]) -> &u64 {
+    oddmedian_bits(s)
+}
+
+/// Median of even sized u64 data
+pub fn evenmedianu64(s: &mut [u64]) -> (&u64, &u64) {
+    evenmedian_bits(s)
+}
+
+/// Indices of the median(s) of u64 `keys`, by binary partitioning of u128 keys that hold
+/// each key in their upper half and its index in their lower half.
+/// Ties are thus broken deterministically by the indices. `keys` must not be empty.
+pub(super) fn argmedian_keys(keys: impl Iterator<Item = u64>) -> ConstMedians<usize> {
+    let mut s: Vec<u128> = keys
+        .enumerate()
+        .map(|(i, key)| ((key as u128) << 64) | i as u128)
+        .collect();
+    // the lower halves are the indices
+    if (s.len() & 1) == 1 {
+        ConstMedians::Odd(*oddmedian_bits(&mut s) as usize)
+    } else {
+        let (&m1, &m2) = evenmedian_bits(&mut s);
+        ConstMedians::Even((m1 as usize, m2 as usize))
     }
 }
 
+/// Indices of the median(s) of `s` by comparisons `c`, selected in a vector of indices.
+/// Ties are broken deterministically by the indices. `s` must not be empty.
+pub(super) fn argmedian_by<T>(
+    s: &[T],
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> ConstMedians<usize> {
+    let n = s.len();
+    let need = (n - 1) / 2; // lower median position
+    let mut idx: Vec<usize> = (0..n).collect();
+    let mut ci = |&i: &usize, &j: &usize| c(&s[i], &s[j]).then(i.cmp(&j));
+    if (n & 1) == 1 {
+        select_mut(&mut idx, need, SelectStrategy::MidOf3, &mut ci);
+        ConstMedians::Odd(idx[need])
+    } else {
+        multiselect_mut(&mut idx, &[need, need + 1], SelectStrategy::MidOf3, &mut ci);
+        ConstMedians::Even((idx[need], idx[need + 1]))
+    }
+}
+
+/// Index of the item of rank `k` (0-based) of `s` by comparisons `c`.
+/// Ties are broken deterministically by the indices.
+pub(super) fn argselect_by<T>(s: &[T], k: usize, c: &mut impl FnMut(&T, &T) -> Ordering) -> usize {
+    let mut idx: Vec<usize> = (0..s.len()).collect();
+    select_mut(
+        &mut idx,
+        k,
+        SelectStrategy::MidOf3,
+        &mut |&i: &usize, &j: &usize| c(&s[i], &s[j]).then(i.cmp(&j)),
+    );
+    idx[k]
+}
+
+/// Items of all the ascending sorted ranks `ks` within `rng` of unsigned keys data,
+/// found in a single descent of binary partitions (recursive).
+/// `diff` is the mask of the bits that differ within `rng`.
+/// Results are pushed into `res` in the order of `ks`.
+pub(super) fn multiselect_bits<K: KeyBits>(
+    s: &mut [K],
+    rng: Range<usize>,
+    ks: &[usize],
+    diff: K,
+    res: &mut Vec<K>,
+) {
+    if ks.is_empty() {
+        return;
+    };
+    if diff == K::ZERO {
+        // all remaining values are the same
+        res.extend(ks.iter().map(|_| s[rng.start]));
+        return;
+    };
+    let (gtsub, ltdiff, gtdiff) = part_bits(s, &rng, diff.highest_bit());
+    let split = ks.partition_point(|&k| k < gtsub);
+    multiselect_bits(s, rng.start..gtsub, &ks[..split], ltdiff, res);
+    multiselect_bits(s, gtsub..rng.end, &ks[split..], gtdiff, res);
+}
+
+/// Byte of u64 `x` at bit offset `shift`, as a histogram index
+fn byte_at(x: u64, shift: u32) -> usize {
+    ((x >> shift) & 0xff) as usize
+}
+
 /// Collects all items that have given byte equal to val
+#[deprecated(since = "3.1.0", note = "no longer used by `medu64_bytes`")]
 pub fn select(bytes: &[[u8; 8]], byteno: usize, val: u8) -> Vec<[u8; 8]> {
     let mut res = Vec::new();
     for &item in bytes {
@@ -347,228 +763,942 @@ pub fn select(bytes: &[[u8; 8]], byteno: usize, val: u8) -> Vec<[u8; 8]> {
     res
 }
 
-/// Median of odd sized u64 data (recursive)
-/// byteno is 0..7 from the most significant (left hand)
-pub(super) fn oddmedu64(bytes: &[[u8; 8]], byteno: usize, need: usize) -> u64 {
-    let n = bytes.len();
-    assert_ne!(n, 0, "oddmedu64 failed to find median");
-    if n == 1 {
-        return u64::from_be_bytes(bytes[0]);
+/// Items of ranks `need` and `need+1` of u64 data by byte radix search, from the most significant byte.  
+/// When `even` is false, only the item of rank `need` is searched for and it is returned twice.  
+/// The candidates are narrowed down level by level within a single reused scratch buffer.
+/// Levels where all the candidates share the same byte do not copy anything.
+pub fn radixselu64(s: &[u64], need: usize, even: bool) -> (u64, u64) {
+    radixselu64_recorded(s, need, even, &mut None)
+}
+
+/// `radixselu64`, adding its work to the stats `st`, when present.
+/// Each byte level is a pass, its byte tests are comparisons and the copies into scratch are swaps.
+pub(super) fn radixselu64_recorded(
+    s: &[u64],
+    need: usize,
+    even: bool,
+    st: &mut Option<&mut SelectStats>,
+) -> (u64, u64) {
+    let mut need = need;
+    let mut scratch: Vec<u64> = Vec::new();
+    let mut narrowed = false; // candidates are in scratch, rather than all of s
+    let mut shift = 56_u32;
+    loop {
+        let cands: &[u64] = if narrowed { &scratch } else { s };
+        let n = cands.len();
+        if n < 16 {
+            // small number of items remaining, just use sort
+            if !narrowed {
+                scratch.extend_from_slice(s);
+                record(st, 0, n, None);
+            };
+            let mut cmps = 0_usize;
+            scratch.sort_unstable_by(counting(&mut |a: &u64, b: &u64| a.cmp(b), &mut cmps));
+            record(st, cmps, 0, Some(0));
+            return (scratch[need], scratch[need + usize::from(even)]);
+        };
+        let mut histogram = [0_usize; 256];
+        for &x in cands {
+            histogram[byte_at(x, shift)] += 1;
+        }
+        let mut below = 0_usize; // number of items in the buckets below the median bucket
+        let mut bucket = 0_usize;
+        while below + histogram[bucket] <= need {
+            below += histogram[bucket];
+            bucket += 1;
+        }
+        let count = histogram[bucket];
+        if even && need + 1 == below + count {
+            // the second item is the minimum of the next non-empty bucket
+            let next = (bucket + 1..256)
+                .find(|&b| histogram[b] > 0)
+                .unwrap_or(bucket);
+            let (mut m1, mut m2) = (0_u64, u64::MAX);
+            for &x in cands {
+                let b = byte_at(x, shift);
+                if b == bucket {
+                    m1 = m1.max(x);
+                } else if b == next {
+                    m2 = m2.min(x);
+                };
+            }
+            record(st, 2 * n, 0, Some(0));
+            return (m1, m2);
+        };
+        if shift == 0 {
+            // termination: the candidates within the bucket are all the same
+            record(st, n, 0, Some(0));
+            let res = (cands[0] & !0xff) | bucket as u64;
+            return (res, res);
+        };
+        need -= below;
+        if count < n {
+            record(st, 2 * n, count, Some(count));
+            if narrowed {
+                scratch.retain(|&x| byte_at(x, shift) == bucket);
+            } else {
+                scratch.reserve_exact(count);
+                scratch.extend(s.iter().filter(|&&x| byte_at(x, shift) == bucket));
+                narrowed = true;
+            };
+        } else {
+            record(st, n, 0, Some(count));
+        };
+        shift -= 8;
+    }
+}
+
+/// Index of the minimum item within a range in a slice,
+/// or of the maximum, when invoked with swapped arguments of comparator c: `|a,b| c(b,a)`
+fn extremum_idx<T>(s: &[T], rng: Range<usize>, c: &mut impl FnMut(&T, &T) -> Ordering) -> usize {
+    let mut m = rng.start;
+    for i in rng.start + 1..rng.end {
+        if c(&s[i], &s[m]) == Less {
+            m = i;
+        };
+    }
+    m
+}
+
+/// Indices of the two smallest items within a range in a slice,
+/// or of the largest two, when invoked with swapped arguments of comparator c: `|a,b| c(b,a)`.
+/// The first returned index always refers to the extremum.
+fn best_two_idx<T>(
+    s: &[T],
+    rng: Range<usize>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> (usize, usize) {
+    let (mut m1, mut m2) = if c(&s[rng.start + 1], &s[rng.start]) == Less {
+        (rng.start + 1, rng.start)
+    } else {
+        (rng.start, rng.start + 1)
     };
-    if n < 8 { // small number of items remaining, just use sort
-        let idx = bytes.isort_refs(0..bytes.len(), |a:&[u8; 8],b| a.cmp(b));
-        return u64::from_be_bytes(*idx[need]);
+    for i in rng.start + 2..rng.end {
+        if c(&s[i], &s[m2]) == Less {
+            if c(&s[i], &s[m1]) == Less {
+                m2 = m1;
+                m1 = i;
+            } else {
+                m2 = i;
+            };
+        };
+    }
+    (m1, m2)
+}
+
+/// Swaps the items at indices `i1` and `i2` into places `p1` and `p2` respectively
+fn place_two<T>(s: &mut [T], (i1, i2): (usize, usize), p1: usize, p2: usize) {
+    s.swap(i1, p1);
+    // the second item may have just been moved out of place p1
+    let i2 = if i2 == p1 { i1 } else { i2 };
+    s.swap(i2, p2);
+}
+
+/// Sorts three consecutive items from `start`, using at most three comparisons.
+/// Returns the number of swaps made
+fn sort3<T>(s: &mut [T], start: usize, c: &mut impl FnMut(&T, &T) -> Ordering) -> usize {
+    let mut swaps = 0;
+    if c(&s[start + 1], &s[start]) == Less {
+        s.swap(start, start + 1);
+        swaps += 1;
     };
-    let mut histogram = [0_usize; 256];
-    let mut cummulator = 0_usize;
-    for &u in bytes {
-        histogram[u[byteno] as usize] += 1;
+    if c(&s[start + 2], &s[start + 1]) == Less {
+        s.swap(start + 1, start + 2);
+        swaps += 1;
+        if c(&s[start + 1], &s[start]) == Less {
+            s.swap(start, start + 1);
+            swaps += 1;
+        };
     };
-    let mut medianbyte = 255_u8;
-    for (i, &h) in histogram.iter().enumerate() {
-        if h == 0_usize { continue };
-        cummulator += h;
-        if cummulator > need {
-            medianbyte = i as u8;
-            cummulator -= h; // number of items < medianbyte
-            break;
-        };
-    }
-    if byteno == 7 {
-        // termination of recursion
-        // bytes are all the same now, except the last one
-        let mut res = bytes[0]; // so can return any item
-        res[7] = medianbyte; // assign the last median byte
-        return u64::from_be_bytes(res);
+    swaps
+}
+
+/// Three way partitioning of `rng` in place, by swaps, around the pivot placed at `rng.start`.
+/// Returns the subscripts `(eqsub, gtsub)`: items in `rng.start..eqsub` are less than the pivot,
+/// in `eqsub..gtsub` equal to it and in `gtsub..rng.end` greater.
+/// Unlike `Mutops::part`, the data remains a permutation of the original.
+pub fn part_mut<T>(
+    s: &mut [T],
+    rng: &Range<usize>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> (usize, usize) {
+    let mut eqsub = rng.start;
+    let mut gtsub = eqsub + 1;
+    for t in rng.start + 1..rng.end {
+        // s[eqsub] is always an item equal to the pivot
+        match c(&s[t], &s[eqsub]) {
+            Less => {
+                s.swap(gtsub, t);
+                s.swap(eqsub, gtsub);
+                eqsub += 1;
+                gtsub += 1;
+            }
+            Equal => {
+                s.swap(gtsub, t);
+                gtsub += 1;
+            }
+            Greater => (),
+        }
+    }
+    (eqsub, gtsub)
+}
+
+/// Index of the pivot estimate within `rng`: mid of three samples,
+/// or, for longer ranges, mid of three such mids
+pub(super) fn pivot_estimate<T>(
+    s: &[T],
+    rng: &Range<usize>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> usize {
+    let mid = rng.start + rng.len() / 2;
+    let pivotsub = midof3(s, rng.start, mid, rng.end - 1, c);
+    if rng.len() > 100 {
+        let pivotsub2 = midof3(s, rng.start + 1, mid + 1, rng.end - 2, c);
+        let pivotsub3 = midof3(s, rng.start + 2, mid + 2, rng.end - 3, c);
+        return midof3(s, pivotsub, pivotsub2, pivotsub3, c);
     };
-    oddmedu64(
-        &select(bytes, byteno, medianbyte),
-        byteno + 1,
-        need - cummulator,
-    ) // tail recursion
+    pivotsub
 }
 
-/*
-/// Median of even sized u64 data (recursive) - work in progress
-pub(super) fn evenmedu64(bytes: &[[u8;8]], byteno:usize, need:usize) -> (u64, u64) {
-    let n = bytes.len();
-    assert_ne!(n,0,"evenmedu64 failed to find median");
-    if n == 2 {
-        let (m1,m2) = (u64::from_be_bytes(bytes[0]),u64::from_be_bytes(bytes[1]));
-        if m2 < m1 { return (m2,m1); } else { return (m1,m2); }; 
+/// Index of the middle valued item of five consecutive ones from `start`,
+/// by insertion sort of their indices
+fn midof5<T>(s: &[T], start: usize, c: &mut impl FnMut(&T, &T) -> Ordering) -> usize {
+    let mut idx = [start, start + 1, start + 2, start + 3, start + 4];
+    for i in 1..5 {
+        let mut j = i;
+        while j > 0 && c(&s[idx[j]], &s[idx[j - 1]]) == Less {
+            idx.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+    idx[2]
+}
+
+/// Index of the median of medians of groups of five within `rng`
+/// (Blum, Floyd, Pratt, Rivest and Tarjan, 1973).
+/// Slower to find than `pivot_estimate` but guaranteed to eliminate at least 3/10 of the range.
+/// The medians are swapped to the front of the range and their median is selected there recursively.
+fn medofmeds<T>(s: &mut [T], rng: &Range<usize>, c: &mut impl FnMut(&T, &T) -> Ordering) -> usize {
+    let groups = rng.len() / 5;
+    for g in 0..groups {
+        let m = midof5(s, rng.start + 5 * g, c);
+        s.swap(rng.start + g, m);
+    }
+    select_guarded(
+        &mut s[rng.start..rng.start + groups],
+        groups / 2,
+        Pivoting::new(SelectStrategy::MidOf3),
+        c,
+        &mut None,
+    );
+    rng.start + groups / 2
+}
+
+/// Index of the recursive mid of mids of three (remedian) of `3^depth` samples,
+/// evenly spaced by `stride` from `start`
+fn remedian<T>(
+    s: &[T],
+    start: usize,
+    stride: usize,
+    depth: u32,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> usize {
+    if depth == 0 {
+        return start;
     };
-    if n < 8 { let sorted_bytes = bytes.isor
-    let mut histogram = [0_usize; 256];
-    let mut cummulator = 0_usize;
-    for &u in bytes {
-        histogram[u[byteno] as usize] += 1;
+    let third = 3_usize.pow(depth - 1) * stride;
+    let m1 = remedian(s, start, stride, depth - 1, c);
+    let m2 = remedian(s, start + third, stride, depth - 1, c);
+    let m3 = remedian(s, start + 2 * third, stride, depth - 1, c);
+    midof3(s, m1, m2, m3, c)
+}
+
+/// Index of the pivot estimate within `rng` by recursive mids of mids of three.
+/// The number of samples is the largest power of three not exceeding the square root of the range length.
+fn midofmids<T>(s: &[T], rng: &Range<usize>, c: &mut impl FnMut(&T, &T) -> Ordering) -> usize {
+    let mut depth = 1_u32;
+    while 3_usize.pow(depth + 1) <= rng.len().isqrt() {
+        depth += 1;
+    }
+    let stride = rng.len() / 3_usize.pow(depth);
+    remedian(s, rng.start, stride, depth, c)
+}
+
+/// Integer cube root (floor), by Newton's iteration
+fn icbrt(n: u128) -> u128 {
+    if n < 8 {
+        return u128::from(n > 0);
     };
-    let mut firstres = true;
-    let mut medianbyte = 255_u8;
-    for (i,h) in histogram.iter().enumerate() {
-        if h == 0_usize { continue };
-        cummulator += h;
-        if firstres {
-            if cummulator > need {
-                medianbyte = i as u8;
-                cummulator -= h; // number of items < medianbyte
-                break;
-                return (i as u64, i as u64);
-            }; // cummulator exceeds need, found both items
-            if need == cummulator {
-                // found first item (last in this bucket)
-                res1 = i as u64;
-                firstres = false;
-            };
-        } else {
-            // the second item is in the first following non-zero bucket
-            return (res1, i as u64);
-        }; // found the second
+    let mut r = 1_u128 << n.ilog2().div_ceil(3); // initial estimate above the root
+    loop {
+        let next = (2 * r + n / (r * r)) / 3;
+        if next >= r {
+            return r;
+        };
+        r = next;
+    }
+}
+
+/// Index of the Floyd-Rivest (1975) pivot estimate for target position `need` within `rng`.
+/// Selects recursively, within a sample subrange of size about `n^(2/3)` around `need`,
+/// the item whose rank in the sample corresponds to `need`. Integer approximations are
+/// used for the sample size and its offset. Short ranges fall back to `pivot_estimate`.
+fn floydrivest<T>(
+    s: &mut [T],
+    rng: &Range<usize>,
+    need: usize,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> usize {
+    let n = rng.len();
+    if n <= 600 {
+        return pivot_estimate(s, rng, c);
     };
-    if firstres { (255,255) } else { (res1,255) }
+    // in 128 bits, as the products below grow as n^(5/3)
+    let i = (need - rng.start) as i128;
+    let ni = n as i128;
+    let z = (7 * n.ilog2() / 10) as i128; // ln(n)
+    let size = (icbrt(n as u128 * n as u128) / 2) as i128; // sample size n^(2/3)/2
+    let sd = (z * size * (ni - size) / ni).unsigned_abs().isqrt() as i128 / 2;
+    let sd = if 2 * i < ni { -sd } else { sd };
+    let left = (need as i128 - i * size / ni + sd).clamp(rng.start as i128, need as i128) as usize;
+    let right = (need as i128 + (ni - i) * size / ni + sd).clamp(need as i128, rng.end as i128 - 1)
+        as usize;
+    select_guarded(
+        &mut s[left..=right],
+        need - left,
+        Pivoting::new(SelectStrategy::FloydRivest),
+        c,
+        &mut None,
+    );
+    need
 }
 
+/// Minimal xorshift pseudo random number generator (Marsaglia, 2003).
+/// `state` must be non zero.
+pub fn xorshift(state: &mut u64) -> u64 {
+    let mut x = *state;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    *state = x;
+    x
+}
 
-        let gtsub = part_binary(s, &rng, bitval);
-        if bitval == 1 {
-            // termination of bit iterations: same values left
-            if need < gtsub - 1 {
-                return (s[gtsub - 2], s[gtsub - 1]);
-            };
-            if need == gtsub - 1 {
-                return (s[gtsub - 1], s[gtsub]);
-            };
-            return (s[gtsub], s[gtsub + 1]);
-        };
-        // well inside lt partition, iterate on it
-        if need + 2 < gtsub {
-            rng.end = gtsub;
-            bitval >>= 1; // next bit
-            continue;
-        };
-        // well inside gt partition, iterate on it
-        if need > gtsub {
-            rng.start = gtsub;
-            bitval >>= 1; // next bit
-            continue;
-        };
-        // penultimate place in lt partition, solution is the maxima pair:
-        if need + 2 == gtsub {
-            return max2u64(s, rng.start..gtsub);
+/// Default seed of the random pivots
+const SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Number of successive unproductive partitions that triggers the median of medians pivot
+const UNPRODUCTIVE_LIMIT: usize = 2;
+
+/// Pivot selection by the given strategy, plus the introselect guard that keeps
+/// the selection in worst-case linear time. The guard counts unproductive partitions,
+/// i.e. those that eliminated less than a quarter of the range.
+/// When they recur, it switches the next pivot to the median of medians.
+#[derive(Clone, Copy)]
+struct Pivoting {
+    strategy: SelectStrategy,
+    lastlen: usize,
+    unproductive: usize,
+    state: u64,
+}
+
+impl Pivoting {
+    fn new(strategy: SelectStrategy) -> Self {
+        Pivoting {
+            strategy,
+            lastlen: usize::MAX,
+            unproductive: 0,
+            state: match strategy {
+                SelectStrategy::SeededSamples(seed) if seed != 0 => seed,
+                _ => SEED,
+            },
+        }
+    }
+    /// Index of the pivot for the next partitioning of `rng`, containing target position `need`.
+    /// The median of medians and Floyd-Rivest strategies reorder items within `rng`.
+    fn pivot<T>(
+        &mut self,
+        s: &mut [T],
+        rng: &Range<usize>,
+        need: usize,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> usize {
+        if rng.len() > self.lastlen - self.lastlen / 4 {
+            self.unproductive += 1;
+        } else {
+            self.unproductive = 0;
         };
-        // last place in the lt partition:
-        if need + 1 == gtsub {
-            return (maxu64(s, rng.start..gtsub), minu64(s, gtsub..rng.end));
+        self.lastlen = rng.len();
+        if rng.len() < 15 {
+            return pivot_estimate(s, rng, c);
         };
-        // first place in gt partition, the solution is its minima pair
-        if need == gtsub {
-            return min2u64(s, gtsub..rng.end);
+        if self.unproductive >= UNPRODUCTIVE_LIMIT {
+            self.unproductive = 0;
+            return medofmeds(s, rng, c);
         };
+        match self.strategy {
+            SelectStrategy::MidOf3 => pivot_estimate(s, rng, c),
+            SelectStrategy::MidOfMids => midofmids(s, rng, c),
+            SelectStrategy::FloydRivest => floydrivest(s, rng, need, c),
+            SelectStrategy::RandomPivot => {
+                rng.start + (xorshift(&mut self.state) % rng.len() as u64) as usize
+            }
+            SelectStrategy::MedianOfMedians => medofmeds(s, rng, c),
+            SelectStrategy::SeededSamples(_) => {
+                let mut sample =
+                    || rng.start + (xorshift(&mut self.state) % rng.len() as u64) as usize;
+                let (i0, i1, i2) = (sample(), sample(), sample());
+                midof3(s, i0, i1, i2, c)
+            }
+        }
     }
 }
-*/
 
-/// Median of odd sized generic data with Odering comparisons by custom closure
-pub(super) fn oddmedian_by<'a, T>(
-    s: &mut [&'a T],
+/// Selects in place the item of rank `k` (0-based) by comparisons `c`, with pivots chosen by `strategy`.
+/// Afterwards `s[k]` holds the item of rank `k`, the items before it are not greater than it
+/// and the items after it are not less than it.
+pub(super) fn select_mut<T>(
+    s: &mut [T],
+    k: usize,
+    strategy: SelectStrategy,
     c: &mut impl FnMut(&T, &T) -> Ordering,
-) -> &'a T {
+) {
+    select_guarded(s, k, Pivoting::new(strategy), c, &mut None)
+}
+
+/// `select_mut` with the given pivoting state, adding its swaps and passes to the stats `st`, when present
+fn select_guarded<T>(
+    s: &mut [T],
+    k: usize,
+    mut pivoting: Pivoting,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+    st: &mut Option<&mut SelectStats>,
+) {
     let mut rng = 0..s.len();
-    let need = s.len() / 2; // first median target position
+    let need = k; // target position in fully partitioned set
     loop {
-        let mut pivotsub = midof3_refs(s, rng.start, rng.start + need, rng.end - 1, c);
+        // minimum or maximum of the range, this includes all ranges of up to two items
+        if need == rng.start {
+            let m = extremum_idx(s, rng, c);
+            s.swap(need, m);
+            record(st, 0, 1, None);
+            return;
+        };
+        if need + 1 == rng.end {
+            let m = extremum_idx(s, rng, &mut |a, b| c(b, a));
+            s.swap(need, m);
+            record(st, 0, 1, None);
+            return;
+        };
         if rng.len() == 3 {
-            return s[pivotsub];
+            let swaps = sort3(s, rng.start, c);
+            record(st, 0, swaps, None);
+            return;
         };
-        if rng.len() > 100 {
-            let pivotsub2 = midof3_refs(s, rng.start + 1, rng.start + need + 1, rng.end - 2, c);
-            let pivotsub3 = midof3_refs(s, rng.start + 2, rng.start + need + 2, rng.end - 3, c);
-            pivotsub = midof3_refs(s, pivotsub, pivotsub2, pivotsub3, c);
-        }
-        if pivotsub != rng.start {
-            s.swap(rng.start, pivotsub);
+        let pivotsub = pivoting.pivot(s, &rng, need, c);
+        s.swap(rng.start, pivotsub);
+        let (eqsub, gtsub) = part_mut(s, &rng, c);
+        // the pivot swap, two swaps for each lesser item and one for each further equal item
+        let swaps = 1 + 2 * (eqsub - rng.start) + (gtsub - eqsub - 1);
+        let remaining = if need < eqsub {
+            eqsub - rng.start
+        } else if need < gtsub {
+            0
+        } else {
+            rng.end - gtsub
         };
-        let pivotref = s[rng.start];
-        let (eqsub, gtsub) = <&mut [T]>::part(s, &rng, c);
+        record(st, 0, swaps, Some(remaining));
         // well inside lt partition, iterate on it
         if need + 2 < eqsub {
             rng.end = eqsub;
             continue;
         };
-        // penultimate place in lt partition, solution:
+        // penultimate place in lt partition, solution is its second maximum
         if need + 2 == eqsub {
-            // swapped comparator arguments to get penultimate maximum
-            return best_two_refs(s, rng.start..eqsub, &mut |a, b| c(b, a)).1;
+            // swapped comparator arguments to get the two maxima
+            let maxs = best_two_idx(s, rng.start..eqsub, &mut |a, b| c(b, a));
+            place_two(s, maxs, eqsub - 1, eqsub - 2);
+            record(st, 0, 2, None);
+            return;
         };
-        // last place in the lt partition, solution is its maximum:
+        // last place in the lt partition, solution is its maximum
         if need + 1 == eqsub {
-            // swapped comparator arguments to get maximum
-            return extremum_refs(s, rng.start..eqsub, &mut |a, b| c(b, a));
+            let m = extremum_idx(s, rng.start..eqsub, &mut |a, b| c(b, a));
+            s.swap(need, m);
+            record(st, 0, 1, None);
+            return;
         };
+        // within equals partition, the pivot is in place
         if need < gtsub {
-            // within equals partition, return the pivot
-            return pivotref;
+            return;
         };
         // first place in gt partition, the solution is its minimum
         if need == gtsub {
-            return extremum_refs(s, gtsub..rng.end, c);
+            let m = extremum_idx(s, gtsub..rng.end, c);
+            s.swap(need, m);
+            record(st, 0, 1, None);
+            return;
         };
         // second place in gt partition, the solution is the next minimum
         if need == gtsub + 1 {
-            return best_two_refs(s, gtsub..rng.end, c).1;
+            let mins = best_two_idx(s, gtsub..rng.end, c);
+            place_two(s, mins, gtsub, gtsub + 1);
+            record(st, 0, 2, None);
+            return;
         };
         // well inside gt partition, iterate on it
         rng.start = gtsub;
     }
 }
 
-/// Median of even sized generic data with Odering comparisons by custom closure
-pub(super) fn evenmedian_by<'a, T>(
-    s: &mut [&'a T],
+/// Sorts in place the window `rng` of the ranks of `s`, by comparisons `c`:
+/// selects its first and last ranks, then sorts only the items between them.
+/// Afterwards `s[rng]` holds the items of those ranks in order, as if `s` were fully sorted.
+pub(super) fn partial_sort_mut<T>(
+    s: &mut [T],
+    rng: Range<usize>,
     c: &mut impl FnMut(&T, &T) -> Ordering,
-) -> (&'a T, &'a T) {
-    let mut rng = 0..s.len();
-    let need = s.len() / 2 - 1; // median target position in fully partitioned set
-    loop {
-        let mut pivotsub = midof3_refs(s, rng.start, rng.start + need, rng.end - 1, c);
-        if rng.len() > 100 {
-            let pivotsub2 = midof3_refs(s, rng.start + 1, rng.start + need + 1, rng.end - 2, c);
-            let pivotsub3 = midof3_refs(s, rng.start + 2, rng.start + need + 2, rng.end - 3, c);
-            pivotsub = midof3_refs(s, pivotsub, pivotsub2, pivotsub3, c);
+) {
+    match rng.len() {
+        0 => (),
+        1 => select_mut(s, rng.start, SelectStrategy::MidOf3, c),
+        _ => {
+            multiselect_mut(s, &[rng.start, rng.end - 1], SelectStrategy::MidOf3, c);
+            s[rng.start + 1..rng.end - 1].sort_unstable_by(c);
+        }
+    };
+}
+
+/// Gathers the items equal to `s[k]` into one run around it, in `s.len()-1` comparisons `c`,
+/// given the nth element property of `s[k]`, as left by `select_mut`.
+/// Returns the subscripts `(eqsub, gtsub)` of the start and the end of the run.
+pub(super) fn equal_run<T>(
+    s: &mut [T],
+    k: usize,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> (usize, usize) {
+    // equal items before k are moved to its left end, so s[k] itself never moves
+    let mut eqsub = k;
+    for i in (0..k).rev() {
+        if c(&s[i], &s[k]) == Equal {
+            eqsub -= 1;
+            s.swap(i, eqsub);
         };
-        if pivotsub != rng.start {
-            s.swap(rng.start, pivotsub);
+    }
+    let mut gtsub = k + 1;
+    for i in k + 1..s.len() {
+        if c(&s[i], &s[k]) == Equal {
+            s.swap(i, gtsub);
+            gtsub += 1;
         };
-        let pivotref = s[rng.start];
-        let (eqsub, gtsub) = <&mut [T]>::part(s, &rng, c);
+    }
+    (eqsub, gtsub)
+}
 
-        // well inside lt partition, iterate on it narrowing the range
-        if need + 2 < eqsub {
+/// Largest data length served by the median networks
+pub const NETWORK_MAX: usize = 25;
+
+/// Comparison/exchange networks that move the median(s) of `n` items to subscripts
+/// `(n-1)/2` and `n/2`, indexed by `n`. Those for 3, 5, 7 and 9 items are the best known
+/// (Paeth, Devillard), the others are Batcher's odd-even merge sorting networks,
+/// pruned of all exchanges that do not affect the medians.
+/// All were verified by the 0-1 principle.
+#[rustfmt::skip]
+const NETWORKS: [&[(u8, u8)]; NETWORK_MAX + 1] = [
+    // 0 items, 0 exchanges
+    &[],
+    // 1 items, 0 exchanges
+    &[],
+    // 2 items, 1 exchanges
+    &[(0, 1)],
+    // 3 items, 3 exchanges
+    &[(0, 1), (1, 2), (0, 1)],
+    // 4 items, 5 exchanges
+    &[(0, 2), (1, 3), (0, 1), (2, 3), (1, 2)],
+    // 5 items, 7 exchanges
+    &[(0, 1), (3, 4), (0, 3), (1, 4), (1, 2), (2, 3), (1, 2)],
+    // 6 items, 12 exchanges
+    &[(0, 4), (1, 5), (0, 2), (1, 3), (2, 4), (3, 5), (0, 1), (2, 3), (4, 5), (1, 4), (1, 2),
+        (3, 4)],
+    // 7 items, 13 exchanges
+    &[(0, 5), (0, 3), (1, 6), (2, 4), (0, 1), (3, 5), (2, 6), (2, 3), (3, 6), (4, 5), (1, 4),
+        (1, 3), (3, 4)],
+    // 8 items, 17 exchanges
+    &[(0, 4), (1, 5), (2, 6), (3, 7), (0, 2), (1, 3), (4, 6), (5, 7), (2, 4), (3, 5), (0, 1),
+        (2, 3), (4, 5), (6, 7), (1, 4), (3, 6), (3, 4)],
+    // 9 items, 19 exchanges
+    &[(1, 2), (4, 5), (7, 8), (0, 1), (3, 4), (6, 7), (1, 2), (4, 5), (7, 8), (0, 3), (5, 8),
+        (4, 7), (3, 6), (1, 4), (2, 5), (4, 7), (2, 4), (4, 6), (2, 4)],
+    // 10 items, 29 exchanges
+    &[(0, 8), (1, 9), (0, 4), (1, 5), (2, 6), (3, 7), (4, 8), (5, 9), (0, 2), (1, 3), (4, 6),
+        (5, 7), (2, 8), (3, 9), (2, 4), (3, 5), (6, 8), (7, 9), (0, 1), (2, 3), (4, 5), (6, 7),
+        (8, 9), (1, 8), (1, 4), (3, 6), (5, 8), (3, 4), (5, 6)],
+    // 11 items, 31 exchanges
+    &[(0, 8), (1, 9), (2, 10), (0, 4), (1, 5), (2, 6), (3, 7), (4, 8), (5, 9), (6, 10), (0, 2),
+        (1, 3), (4, 6), (5, 7), (8, 10), (2, 8), (3, 9), (2, 4), (3, 5), (6, 8), (7, 9),
+        (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (1, 8), (3, 10), (3, 6), (5, 8), (5, 6)],
+    // 12 items, 35 exchanges
+    &[(0, 8), (1, 9), (2, 10), (3, 11), (0, 4), (1, 5), (2, 6), (3, 7), (4, 8), (5, 9),
+        (6, 10), (7, 11), (0, 2), (1, 3), (4, 6), (5, 7), (8, 10), (9, 11), (2, 8), (3, 9),
+        (2, 4), (3, 5), (6, 8), (7, 9), (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11),
+        (1, 8), (3, 10), (3, 6), (5, 8), (5, 6)],
+    // 13 items, 40 exchanges
+    &[(0, 8), (1, 9), (2, 10), (3, 11), (4, 12), (0, 4), (1, 5), (2, 6), (3, 7), (8, 12),
+        (4, 8), (5, 9), (6, 10), (7, 11), (0, 2), (1, 3), (4, 6), (5, 7), (8, 10), (9, 11),
+        (2, 8), (3, 9), (6, 12), (2, 4), (3, 5), (6, 8), (7, 9), (10, 12), (0, 1), (2, 3),
+        (4, 5), (6, 7), (8, 9), (10, 11), (1, 8), (3, 10), (5, 12), (3, 6), (5, 8), (5, 6)],
+    // 14 items, 47 exchanges
+    &[(0, 8), (1, 9), (2, 10), (3, 11), (4, 12), (5, 13), (0, 4), (1, 5), (2, 6), (3, 7),
+        (8, 12), (9, 13), (4, 8), (5, 9), (6, 10), (7, 11), (0, 2), (1, 3), (4, 6), (5, 7),
+        (8, 10), (9, 11), (2, 8), (3, 9), (6, 12), (7, 13), (2, 4), (3, 5), (6, 8), (7, 9),
+        (10, 12), (11, 13), (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (1, 8),
+        (3, 10), (5, 12), (3, 6), (5, 8), (7, 10), (5, 6), (7, 8)],
+    // 15 items, 49 exchanges
+    &[(0, 8), (1, 9), (2, 10), (3, 11), (4, 12), (5, 13), (6, 14), (0, 4), (1, 5), (2, 6),
+        (3, 7), (8, 12), (9, 13), (10, 14), (4, 8), (5, 9), (6, 10), (7, 11), (0, 2), (1, 3),
+        (4, 6), (5, 7), (8, 10), (9, 11), (12, 14), (2, 8), (3, 9), (6, 12), (7, 13), (2, 4),
+        (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (0, 1), (2, 3), (4, 5), (6, 7), (8, 9),
+        (10, 11), (12, 13), (1, 8), (3, 10), (5, 12), (7, 14), (5, 8), (7, 10), (7, 8)],
+    // 16 items, 53 exchanges
+    &[(0, 8), (1, 9), (2, 10), (3, 11), (4, 12), (5, 13), (6, 14), (7, 15), (0, 4), (1, 5),
+        (2, 6), (3, 7), (8, 12), (9, 13), (10, 14), (11, 15), (4, 8), (5, 9), (6, 10), (7, 11),
+        (0, 2), (1, 3), (4, 6), (5, 7), (8, 10), (9, 11), (12, 14), (13, 15), (2, 8), (3, 9),
+        (6, 12), (7, 13), (2, 4), (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (0, 1), (2, 3),
+        (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (1, 8), (3, 10), (5, 12),
+        (7, 14), (5, 8), (7, 10), (7, 8)],
+    // 17 items, 61 exchanges
+    &[(0, 16), (0, 8), (1, 9), (2, 10), (3, 11), (4, 12), (5, 13), (6, 14), (7, 15), (8, 16),
+        (0, 4), (1, 5), (2, 6), (3, 7), (8, 12), (9, 13), (10, 14), (11, 15), (4, 16), (4, 8),
+        (5, 9), (6, 10), (7, 11), (12, 16), (0, 2), (1, 3), (4, 6), (5, 7), (8, 10), (9, 11),
+        (12, 14), (13, 15), (2, 16), (2, 8), (3, 9), (6, 12), (7, 13), (10, 16), (2, 4),
+        (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (14, 16), (0, 1), (2, 3), (4, 5), (6, 7),
+        (8, 9), (10, 11), (12, 13), (14, 15), (1, 16), (1, 8), (3, 10), (5, 12), (7, 14),
+        (5, 8), (7, 10), (7, 8)],
+    // 18 items, 72 exchanges
+    &[(0, 16), (1, 17), (0, 8), (1, 9), (2, 10), (3, 11), (4, 12), (5, 13), (6, 14), (7, 15),
+        (8, 16), (9, 17), (0, 4), (1, 5), (2, 6), (3, 7), (8, 12), (9, 13), (10, 14), (11, 15),
+        (4, 16), (5, 17), (4, 8), (5, 9), (6, 10), (7, 11), (12, 16), (13, 17), (0, 2), (1, 3),
+        (4, 6), (5, 7), (8, 10), (9, 11), (12, 14), (13, 15), (2, 16), (3, 17), (2, 8), (3, 9),
+        (6, 12), (7, 13), (10, 16), (11, 17), (2, 4), (3, 5), (6, 8), (7, 9), (10, 12),
+        (11, 13), (14, 16), (15, 17), (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11),
+        (12, 13), (14, 15), (16, 17), (1, 16), (1, 8), (3, 10), (5, 12), (7, 14), (9, 16),
+        (5, 8), (7, 10), (9, 12), (7, 8), (9, 10)],
+    // 19 items, 75 exchanges
+    &[(0, 16), (1, 17), (2, 18), (0, 8), (1, 9), (2, 10), (3, 11), (4, 12), (5, 13), (6, 14),
+        (7, 15), (8, 16), (9, 17), (10, 18), (0, 4), (1, 5), (2, 6), (3, 7), (8, 12), (9, 13),
+        (10, 14), (11, 15), (4, 16), (5, 17), (6, 18), (4, 8), (5, 9), (6, 10), (7, 11),
+        (12, 16), (13, 17), (14, 18), (0, 2), (1, 3), (4, 6), (5, 7), (8, 10), (9, 11),
+        (12, 14), (13, 15), (16, 18), (2, 16), (3, 17), (2, 8), (3, 9), (6, 12), (7, 13),
+        (10, 16), (11, 17), (2, 4), (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (14, 16),
+        (15, 17), (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15),
+        (16, 17), (1, 16), (3, 18), (3, 10), (5, 12), (7, 14), (9, 16), (7, 10), (9, 12),
+        (9, 10)],
+    // 20 items, 81 exchanges
+    &[(0, 16), (1, 17), (2, 18), (3, 19), (0, 8), (1, 9), (2, 10), (3, 11), (4, 12), (5, 13),
+        (6, 14), (7, 15), (8, 16), (9, 17), (10, 18), (11, 19), (0, 4), (1, 5), (2, 6), (3, 7),
+        (8, 12), (9, 13), (10, 14), (11, 15), (4, 16), (5, 17), (6, 18), (7, 19), (4, 8),
+        (5, 9), (6, 10), (7, 11), (12, 16), (13, 17), (14, 18), (15, 19), (0, 2), (1, 3),
+        (4, 6), (5, 7), (8, 10), (9, 11), (12, 14), (13, 15), (16, 18), (17, 19), (2, 16),
+        (3, 17), (2, 8), (3, 9), (6, 12), (7, 13), (10, 16), (11, 17), (2, 4), (3, 5), (6, 8),
+        (7, 9), (10, 12), (11, 13), (14, 16), (15, 17), (0, 1), (2, 3), (4, 5), (6, 7), (8, 9),
+        (10, 11), (12, 13), (14, 15), (16, 17), (18, 19), (1, 16), (3, 18), (3, 10), (5, 12),
+        (7, 14), (9, 16), (7, 10), (9, 12), (9, 10)],
+    // 21 items, 88 exchanges
+    &[(0, 16), (1, 17), (2, 18), (3, 19), (4, 20), (0, 8), (1, 9), (2, 10), (3, 11), (4, 12),
+        (5, 13), (6, 14), (7, 15), (8, 16), (9, 17), (10, 18), (11, 19), (12, 20), (0, 4),
+        (1, 5), (2, 6), (3, 7), (8, 12), (9, 13), (10, 14), (11, 15), (16, 20), (4, 16),
+        (5, 17), (6, 18), (7, 19), (4, 8), (5, 9), (6, 10), (7, 11), (12, 16), (13, 17),
+        (14, 18), (15, 19), (0, 2), (1, 3), (4, 6), (5, 7), (8, 10), (9, 11), (12, 14),
+        (13, 15), (16, 18), (17, 19), (2, 16), (3, 17), (6, 20), (2, 8), (3, 9), (6, 12),
+        (7, 13), (10, 16), (11, 17), (14, 20), (2, 4), (3, 5), (6, 8), (7, 9), (10, 12),
+        (11, 13), (14, 16), (15, 17), (18, 20), (0, 1), (2, 3), (4, 5), (6, 7), (8, 9),
+        (10, 11), (12, 13), (14, 15), (16, 17), (18, 19), (1, 16), (3, 18), (5, 20), (3, 10),
+        (5, 12), (7, 14), (9, 16), (7, 10), (9, 12), (9, 10)],
+    // 22 items, 98 exchanges
+    &[(0, 16), (1, 17), (2, 18), (3, 19), (4, 20), (5, 21), (0, 8), (1, 9), (2, 10), (3, 11),
+        (4, 12), (5, 13), (6, 14), (7, 15), (8, 16), (9, 17), (10, 18), (11, 19), (12, 20),
+        (13, 21), (0, 4), (1, 5), (2, 6), (3, 7), (8, 12), (9, 13), (10, 14), (11, 15),
+        (16, 20), (17, 21), (4, 16), (5, 17), (6, 18), (7, 19), (4, 8), (5, 9), (6, 10),
+        (7, 11), (12, 16), (13, 17), (14, 18), (15, 19), (0, 2), (1, 3), (4, 6), (5, 7),
+        (8, 10), (9, 11), (12, 14), (13, 15), (16, 18), (17, 19), (2, 16), (3, 17), (6, 20),
+        (7, 21), (2, 8), (3, 9), (6, 12), (7, 13), (10, 16), (11, 17), (14, 20), (15, 21),
+        (2, 4), (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (14, 16), (15, 17), (18, 20),
+        (19, 21), (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15),
+        (16, 17), (18, 19), (20, 21), (1, 16), (3, 18), (5, 20), (3, 10), (5, 12), (7, 14),
+        (9, 16), (11, 18), (7, 10), (9, 12), (11, 14), (9, 10), (11, 12)],
+    // 23 items, 100 exchanges
+    &[(0, 16), (1, 17), (2, 18), (3, 19), (4, 20), (5, 21), (6, 22), (0, 8), (1, 9), (2, 10),
+        (3, 11), (4, 12), (5, 13), (6, 14), (7, 15), (8, 16), (9, 17), (10, 18), (11, 19),
+        (12, 20), (13, 21), (14, 22), (0, 4), (1, 5), (2, 6), (3, 7), (8, 12), (9, 13),
+        (10, 14), (11, 15), (16, 20), (17, 21), (18, 22), (4, 16), (5, 17), (6, 18), (7, 19),
+        (4, 8), (5, 9), (6, 10), (7, 11), (12, 16), (13, 17), (14, 18), (15, 19), (0, 2),
+        (1, 3), (4, 6), (5, 7), (8, 10), (9, 11), (12, 14), (13, 15), (16, 18), (17, 19),
+        (20, 22), (2, 16), (3, 17), (6, 20), (7, 21), (2, 8), (3, 9), (6, 12), (7, 13),
+        (10, 16), (11, 17), (14, 20), (15, 21), (2, 4), (3, 5), (6, 8), (7, 9), (10, 12),
+        (11, 13), (14, 16), (15, 17), (18, 20), (19, 21), (0, 1), (2, 3), (4, 5), (6, 7),
+        (8, 9), (10, 11), (12, 13), (14, 15), (16, 17), (18, 19), (20, 21), (1, 16), (3, 18),
+        (5, 20), (7, 22), (5, 12), (7, 14), (9, 16), (11, 18), (9, 12), (11, 14), (11, 12)],
+    // 24 items, 105 exchanges
+    &[(0, 16), (1, 17), (2, 18), (3, 19), (4, 20), (5, 21), (6, 22), (7, 23), (0, 8), (1, 9),
+        (2, 10), (3, 11), (4, 12), (5, 13), (6, 14), (7, 15), (8, 16), (9, 17), (10, 18),
+        (11, 19), (12, 20), (13, 21), (14, 22), (15, 23), (0, 4), (1, 5), (2, 6), (3, 7),
+        (8, 12), (9, 13), (10, 14), (11, 15), (16, 20), (17, 21), (18, 22), (19, 23), (4, 16),
+        (5, 17), (6, 18), (7, 19), (4, 8), (5, 9), (6, 10), (7, 11), (12, 16), (13, 17),
+        (14, 18), (15, 19), (0, 2), (1, 3), (4, 6), (5, 7), (8, 10), (9, 11), (12, 14),
+        (13, 15), (16, 18), (17, 19), (20, 22), (21, 23), (2, 16), (3, 17), (6, 20), (7, 21),
+        (2, 8), (3, 9), (6, 12), (7, 13), (10, 16), (11, 17), (14, 20), (15, 21), (2, 4),
+        (3, 5), (6, 8), (7, 9), (10, 12), (11, 13), (14, 16), (15, 17), (18, 20), (19, 21),
+        (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17),
+        (18, 19), (20, 21), (22, 23), (1, 16), (3, 18), (5, 20), (7, 22), (5, 12), (7, 14),
+        (9, 16), (11, 18), (9, 12), (11, 14), (11, 12)],
+    // 25 items, 113 exchanges
+    &[(0, 16), (1, 17), (2, 18), (3, 19), (4, 20), (5, 21), (6, 22), (7, 23), (8, 24), (0, 8),
+        (1, 9), (2, 10), (3, 11), (4, 12), (5, 13), (6, 14), (7, 15), (16, 24), (8, 16),
+        (9, 17), (10, 18), (11, 19), (12, 20), (13, 21), (14, 22), (15, 23), (0, 4), (1, 5),
+        (2, 6), (3, 7), (8, 12), (9, 13), (10, 14), (11, 15), (16, 20), (17, 21), (18, 22),
+        (19, 23), (4, 16), (5, 17), (6, 18), (7, 19), (12, 24), (4, 8), (5, 9), (6, 10),
+        (7, 11), (12, 16), (13, 17), (14, 18), (15, 19), (20, 24), (0, 2), (1, 3), (4, 6),
+        (5, 7), (8, 10), (9, 11), (12, 14), (13, 15), (16, 18), (17, 19), (20, 22), (21, 23),
+        (2, 16), (3, 17), (6, 20), (7, 21), (10, 24), (2, 8), (3, 9), (6, 12), (7, 13),
+        (10, 16), (11, 17), (14, 20), (15, 21), (18, 24), (2, 4), (3, 5), (6, 8), (7, 9),
+        (10, 12), (11, 13), (14, 16), (15, 17), (18, 20), (19, 21), (22, 24), (0, 1), (2, 3),
+        (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (14, 15), (16, 17), (18, 19), (20, 21),
+        (22, 23), (1, 16), (3, 18), (5, 20), (7, 22), (9, 24), (5, 12), (7, 14), (9, 16),
+        (11, 18), (9, 12), (11, 14), (11, 12)],
+];
+
+/// Median(s) of up to `NETWORK_MAX` (and at least one) items, selected in place
+/// by the median network of their length, using comparisons `c`.
+/// Returns the lower and the upper median, which are the same item for odd lengths.
+pub(super) fn median_network<T: Copy>(
+    s: &mut [T],
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> (T, T) {
+    let n = s.len();
+    for &(i, j) in NETWORKS[n] {
+        let (i, j) = (i as usize, j as usize);
+        let (x, y) = (s[i], s[j]);
+        // exchange without branching, when the comparator allows it
+        let lt = c(&y, &x) == Less;
+        s[i] = if lt { y } else { x };
+        s[j] = if lt { x } else { y };
+    }
+    (s[(n - 1) / 2], s[n / 2])
+}
+
+/// `median_network`, adding its comparisons and exchanges to the stats `st`.
+/// A network makes no partition passes.
+pub(super) fn median_network_recorded<T: Copy>(
+    s: &mut [T],
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+    st: &mut SelectStats,
+) -> (T, T) {
+    let (mut cmps, mut swaps) = (0_usize, 0_usize);
+    // each comparison decides one exchange, which swaps when it returns `Less`
+    let res = median_network(s, &mut |a: &T, b: &T| {
+        cmps += 1;
+        let ord = c(a, b);
+        swaps += usize::from(ord == Less);
+        ord
+    });
+    record(&mut Some(st), cmps, swaps, None);
+    res
+}
+
+/// Range length below which `medians_block` just sorts the range
+const BLOCK_SORT: usize = 16;
+
+/// Items of ranks `need` and `hi` (either `need` or `need+1`) of primitive data,
+/// selected in place by comparisons `c`. Partitions around a copied pivot value by the branch
+/// reduced `block_part`, in two passes: first the lesser items, then, only when the target
+/// ranks are not among them, the equal ones. The introselect guard applies as in `select_mut`.
+pub(super) fn medians_block<T: Copy>(
+    s: &mut [T],
+    need: usize,
+    hi: usize,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> (T, T) {
+    let mut rng = 0..s.len();
+    let mut pivoting = Pivoting::new(SelectStrategy::MidOf3);
+    loop {
+        if rng.len() <= BLOCK_SORT {
+            s[rng].sort_unstable_by(&mut *c);
+            return (s[need], s[hi]);
+        };
+        let pivot = s[pivoting.pivot(s, &rng, need, c)];
+        let eqsub = block_part(s, rng.clone(), |x| c(x, &pivot) == Less);
+        // both targets in lt partition, iterate on it
+        if hi < eqsub {
             rng.end = eqsub;
             continue;
         };
-        // penultimate place in lt partition, return its two maxima:
-        if need + 2 == eqsub {
-            let (m1, m2) = best_two_refs(s, rng.start..eqsub, &mut |a, b| c(b, a));
-            return (m2,m1); // in ascending order
-        };
-        // last place in the lt partition, return its maximum and pivot:
-        if need + 1 == eqsub {
-            return (extremum_refs(s, rng.start..eqsub, &mut |a, b| c(b, a)), pivotref);
-        };
-        // fully within equals partition
-        if need + 1 < gtsub {
-            return (pivotref, pivotref);
+        // split at the end of lt partition: its maximum and the pivot, the minimum of the rest
+        if need < eqsub {
+            let m = extremum_idx(s, rng.start..eqsub, &mut |a, b| c(b, a));
+            return (s[m], pivot);
         };
-        // last place in equals partition, return pivot and minimum of gt partition:
-        if need + 1 == gtsub {
-            return (pivotref, extremum_refs(s, gtsub..rng.end, c));
+        let gtsub = block_part(s, eqsub..rng.end, |x| c(x, &pivot) != Greater);
+        // both targets within equals partition
+        if hi < gtsub {
+            return (pivot, pivot);
         };
-        // first place in gt partition, return its two minima
-        if need == gtsub {
-            return best_two_refs(s, gtsub..rng.end, c);
+        // split at the end of equals partition: the pivot and the minimum of gt partition
+        if need < gtsub {
+            let m = extremum_idx(s, gtsub..rng.end, c);
+            return (pivot, s[m]);
         };
-        // inside gt partition, iterate on it, narrowing the range
+        // both targets in gt partition, iterate on it
         rng.start = gtsub;
     }
 }
+
+/// Selects in place the items of all the ascending sorted ranks `ks`, in a single descent
+/// (recursive), by comparisons `c`, with pivots chosen by `strategy`. Each partition is shared
+/// between all the ranks that fall within it. Afterwards each `s[k]` holds the item of rank `k`.
+pub(super) fn multiselect_mut<T>(
+    s: &mut [T],
+    ks: &[usize],
+    strategy: SelectStrategy,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) {
+    let n = s.len();
+    multiselect_guarded(s, 0..n, ks, Pivoting::new(strategy), c, &mut None)
+}
+
+/// Selects in place the items of all the ascending sorted ranks `ks`, as `multiselect_mut`
+/// with the default pivots, recording all its work in `st`.
+/// The comparisons are counted by wrapping `c`.
+pub(super) fn multiselect_recorded<T>(
+    s: &mut [T],
+    ks: &[usize],
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+    st: &mut SelectStats,
+) {
+    let n = s.len();
+    let mut cmps = 0_usize;
+    multiselect_guarded(
+        s,
+        0..n,
+        ks,
+        Pivoting::new(SelectStrategy::MidOf3),
+        &mut counting(c, &mut cmps),
+        &mut Some(st),
+    );
+    st.comparisons += cmps;
+}
+
+/// `multiselect_mut` within `rng`, with the pivoting state passed down the recursion.
+/// Adds its swaps and passes to the stats `st`, when present.
+fn multiselect_guarded<T>(
+    s: &mut [T],
+    rng: Range<usize>,
+    ks: &[usize],
+    mut pivoting: Pivoting,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+    st: &mut Option<&mut SelectStats>,
+) {
+    match ks.len() {
+        0 => return,
+        1 => {
+            // a single rank remaining, finish it within its subrange
+            select_guarded(&mut s[rng.clone()], ks[0] - rng.start, pivoting, c, st);
+            return;
+        }
+        _ => (),
+    };
+    let pivotsub = pivoting.pivot(s, &rng, ks[ks.len() / 2], c);
+    s.swap(rng.start, pivotsub);
+    let (eqsub, gtsub) = part_mut(s, &rng, c);
+    let ltend = ks.partition_point(|&k| k < eqsub);
+    let eqend = ks.partition_point(|&k| k < gtsub);
+    if st.is_some() {
+        // in play are the partitions that still contain some ranks
+        let mut remaining = 0;
+        if ltend > 0 {
+            remaining += eqsub - rng.start;
+        };
+        if eqend < ks.len() {
+            remaining += rng.end - gtsub;
+        };
+        record(
+            st,
+            0,
+            1 + 2 * (eqsub - rng.start) + (gtsub - eqsub - 1),
+            Some(remaining),
+        );
+    };
+    multiselect_guarded(s, rng.start..eqsub, &ks[..ltend], pivoting, c, st);
+    multiselect_guarded(s, gtsub..rng.end, &ks[eqend..], pivoting, c, st);
+}
+
+/// Item of rank `k` (0-based) in generic data with Ordering comparisons by custom closure.  
+/// Generalises `oddmedian_by`, which is this function with `k = s.len()/2`.
+pub(super) fn select_by<'a, T>(
+    s: &mut [&'a T],
+    k: usize,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> &'a T {
+    select_with(s, k, SelectStrategy::MidOf3, c)
+}
+
+/// Item of rank `k` (0-based) by comparisons `c`, with pivots chosen by `strategy`
+pub(super) fn select_with<'a, T>(
+    s: &mut [&'a T],
+    k: usize,
+    strategy: SelectStrategy,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> &'a T {
+    select_mut(s, k, strategy, &mut |a: &&T, b: &&T| c(a, b));
+    s[k]
+}
+
+/// Items of all the ascending sorted ranks `ks` within `rng`, found in a single
+/// descent (recursive), by comparisons `c`. Each partition is shared between all the ranks
+/// that fall within it. Results are pushed into `res` in the order of `ks`.
+pub(super) fn multiselect_by<'a, T>(
+    s: &mut [&'a T],
+    rng: Range<usize>,
+    ks: &[usize],
+    res: &mut Vec<&'a T>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) {
+    multiselect_guarded(
+        s,
+        rng,
+        ks,
+        Pivoting::new(SelectStrategy::MidOf3),
+        &mut |a: &&T, b: &&T| c(a, b),
+        &mut None,
+    );
+    res.extend(ks.iter().map(|&k| s[k]));
+}
+
+/// Median of odd sized generic data with Odering comparisons by custom closure
+pub(super) fn oddmedian_by<'a, T>(
+    s: &mut [&'a T],
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> &'a T {
+    let need = s.len() / 2; // median target position
+    select_by(s, need, c)
+}
+
+/// Median of even sized generic data with Odering comparisons by custom closure
+pub(super) fn evenmedian_by<'a, T>(
+    s: &mut [&'a T],
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> (&'a T, &'a T) {
+    evenmedian_with(s, SelectStrategy::MidOf3, c)
+}
+
+/// Median of even sized generic data by comparisons `c`, with pivots chosen by `strategy`
+pub(super) fn evenmedian_with<'a, T>(
+    s: &mut [&'a T],
+    strategy: SelectStrategy,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> (&'a T, &'a T) {
+    let need = s.len() / 2 - 1; // lower median target position in fully partitioned set
+    multiselect_mut(s, &[need, need + 1], strategy, &mut |a: &&T, b: &&T| {
+        c(a, b)
+    });
+    (s[need], s[need + 1])
+}
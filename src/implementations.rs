@@ -1,9 +1,19 @@
-use std::error::Error;
-use core::{fmt,cmp::Ordering};
+use alloc::{format, vec::Vec};
+use core::error::Error;
 use core::fmt::{Debug, Display};
+use core::{cmp::Ordering, fmt};
 
-use indxvec::{Vecops,printing::{GR, UN, YL}};
-    use crate::{*,algos::*};
+use crate::{algos::*, *};
+#[cfg(feature = "std")]
+use indxvec::printing::{GR, UN, YL};
+
+/// Without std, the medians are displayed without the colours of indxvec
+#[cfg(not(feature = "std"))]
+const GR: &str = "";
+#[cfg(not(feature = "std"))]
+const UN: &str = "";
+#[cfg(not(feature = "std"))]
+const YL: &str = "";
 
 impl<T> Error for MedError<T> where T: Sized + Debug + Display {}
 
@@ -13,38 +23,154 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            MedError::Size(s) => write!(f, "Size of data must be positive: {s}"),            
-            MedError::Nan(s) => write!(f, "Floats must not include NaNs: {s}"), 
+            MedError::Size(s) => write!(f, "Size of data must be positive: {s}"),
+            MedError::Nan(s) => write!(f, "Floats must not include NaNs: {s}"),
             MedError::Other(s) => write!(f, "Converted from: {s}"),
         }
     }
 }
 
-impl<T> std::fmt::Display for Medians<'_, T>
+impl<T> Display for Medians<'_, T>
 where
     T: Display,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Medians::Odd(m) => {
                 write!(f, "{YL}odd median: {GR}{}{UN}", *m)
             }
-            Medians::Even((m1,m2)) => {
+            Medians::Even((m1, m2)) => {
                 write!(f, "{YL}even medians: {GR}{} {}{UN}", *m1, *m2)
             }
         }
     }
 }
 
+impl<T> Display for ConstMedians<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstMedians::Odd(m) => {
+                write!(f, "{YL}odd median: {GR}{}{UN}", m)
+            }
+            ConstMedians::Even((m1, m2)) => {
+                write!(f, "{YL}even medians: {GR}{} {}{UN}", m1, m2)
+            }
+        }
+    }
+}
+
+/// Medians of up to `NETWORK_MAX` floats, copied to the stack as their order preserving keys
+/// and selected there by their median network. The integer keys are exchanged without branching,
+/// unlike the floats themselves. Their order is that of `total_cmp`.
+fn network_medians<F: RadixKey>(s: &[F]) -> (F, F) {
+    let n = s.len();
+    let mut keys = [<F::Key as KeyBits>::ZERO; NETWORK_MAX];
+    for (k, &x) in keys.iter_mut().zip(s) {
+        *k = x.to_key();
+    }
+    let (k1, k2) = median_network(&mut keys[..n], &mut <F::Key>::cmp);
+    (F::from_key(k1), F::from_key(k2))
+}
+
+impl<T> From<ConstMedians<T>> for f64
+where
+    T: core::convert::Into<u64>,
+{
+    fn from(item: ConstMedians<T>) -> f64 {
+        match item {
+            ConstMedians::Odd(m) => m.into() as f64,
+            ConstMedians::Even((m1, m2)) => (m1.into() as f64 + m2.into() as f64) / 2.0,
+        }
+    }
+}
+
 impl<T> From<Medians<'_, T>> for f64
-where T: Copy+std::convert::Into<u64>
+where
+    T: Copy + core::convert::Into<u64>,
 {
-    fn from(item:Medians<T>) -> f64 {
+    fn from(item: Medians<T>) -> f64 {
         match item {
             Medians::Odd(&m) => m.into() as f64,
-            Medians::Even((&m1, &m2)) => (m1.into() as f64 + m2.into() as f64)/ 2.0
+            Medians::Even((&m1, &m2)) => (m1.into() as f64 + m2.into() as f64) / 2.0,
+        }
+    }
+}
+
+macro_rules! impl_radixkey_unsigned {
+    ($($t:ty),*) => {$(
+        /// Unsigned integers are their own keys
+        impl RadixKey for $t {
+            type Key = $t;
+            fn to_key(self) -> Self::Key {
+                self
+            }
+            fn from_key(key: Self::Key) -> Self {
+                key
+            }
+        }
+    )*};
+}
+impl_radixkey_unsigned!(u8, u16, u32, u64, u128, usize);
+
+macro_rules! impl_radixkey_signed {
+    ($($t:ty => $k:ty),*) => {$(
+        /// Signed integers map to unsigned keys by flipping the sign bit
+        impl RadixKey for $t {
+            type Key = $k;
+            fn to_key(self) -> Self::Key {
+                (self as $k) ^ (1 << (<$k>::BITS - 1))
+            }
+            fn from_key(key: Self::Key) -> Self {
+                (key ^ (1 << (<$k>::BITS - 1))) as $t
+            }
+        }
+    )*};
+}
+impl_radixkey_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+/// Floats map to unsigned keys ordered as by `total_cmp`:
+/// negative numbers have all their bits flipped, positive ones only the sign bit.
+/// NaNs with the sign bit set go below `-inf`, the others above `inf`.
+impl RadixKey for f64 {
+    type Key = u64;
+    fn to_key(self) -> Self::Key {
+        let u = self.to_bits();
+        if (u >> 63) == 1 {
+            !u
+        } else {
+            u ^ FIRST_BIT
+        }
+    }
+    fn from_key(key: Self::Key) -> Self {
+        f64::from_bits(if (key >> 63) == 1 {
+            key ^ FIRST_BIT
+        } else {
+            !key
+        })
+    }
+}
+
+/// As for f64
+impl RadixKey for f32 {
+    type Key = u32;
+    fn to_key(self) -> Self::Key {
+        let u = self.to_bits();
+        if (u >> 31) == 1 {
+            !u
+        } else {
+            u ^ (1 << 31)
         }
     }
+    fn from_key(key: Self::Key) -> Self {
+        f32::from_bits(if (key >> 31) == 1 {
+            key ^ (1 << 31)
+        } else {
+            !key
+        })
+    }
 }
 
 /// Medians of &mut [&f64].
@@ -52,29 +178,59 @@ impl Medianf64 for &[f64] {
     /// Returns `nan` error when any data item is a NaN, otherwise the median
     fn medf_checked(self) -> Result<f64, Me> {
         let n = self.len();
+        if self.iter().any(|x| x.is_nan()) {
+            return merror("nan", "medf_checked: Nan in input!");
+        };
         match n {
             0 => return merror("size", "medf_checked: zero length data"),
             1 => return Ok(self[0]),
             2 => return Ok((self[0] + self[1]) / 2.0),
             _ => (),
         };
-        let mut s = self
-        .iter()
-        .map(|x| {
-            if x.is_nan() {
-                merror("Nan", "medf_checked: Nan in input!")
-            } else {
-                Ok(x)
-            }
-        }).collect::<Result<Vec<&f64>, Me>>()?;
+        let (med1, med2) = if n <= NETWORK_MAX {
+            // few items, copied to the stack and selected by their median network
+            network_medians(self)
+        } else {
+            // the values themselves are selected, by the branch reduced block partitioning
+            medians_block(
+                &mut self.to_vec(),
+                (n - 1) / 2,
+                n / 2,
+                &mut <f64>::total_cmp,
+            )
+        };
+        if (n & 1) == 1 {
+            Ok(med1)
+        } else {
+            Ok((med1 + med2) / 2.0)
+        }
+    }
+
+    /// Median by binary partitioning of order preserving u64 keys of the data (see `RadixKey`),
+    /// rather than by comparisons. Returns `nan` error when any data item is a NaN, as `medf_checked`.
+    fn medf_radix(self) -> Result<f64, Me> {
+        let n = self.len();
+        if n == 0 {
+            return merror("size", "medf_radix: zero length data");
+        };
+        let mut keys = self
+            .iter()
+            .map(|&x| {
+                if x.is_nan() {
+                    merror("nan", "medf_radix: Nan in input!")
+                } else {
+                    Ok(x.to_key())
+                }
+            })
+            .collect::<Result<Vec<u64>, Me>>()?;
         if (n & 1) == 1 {
-            Ok(*oddmedian_by(&mut s, &mut <f64>::total_cmp)) 
+            Ok(f64::from_key(*oddmedian_bits(&mut keys)))
         } else {
-            let (&med1, &med2) = evenmedian_by(&mut s, &mut <f64>::total_cmp);
-            Ok((med1+med2) / 2.0)
+            let (&k1, &k2) = evenmedian_bits(&mut keys);
+            Ok((f64::from_key(k1) + f64::from_key(k2)) / 2.0)
         }
     }
- 
+
     /// Use this when your data does not contain any NaNs.
     /// NaNs will not raise an error. However, they will affect the result
     /// because of their order positions beyond infinity.
@@ -86,39 +242,56 @@ impl Medianf64 for &[f64] {
             2 => return (self[0] + self[1]) / 2.0,
             _ => (),
         };
-        let mut s = self.ref_vec(0..self.len());
+        let (med1, med2) = if n <= NETWORK_MAX {
+            network_medians(self)
+        } else {
+            medians_block(
+                &mut self.to_vec(),
+                (n - 1) / 2,
+                n / 2,
+                &mut <f64>::total_cmp,
+            )
+        };
         if (n & 1) == 1 {
-            *oddmedian_by(&mut s, &mut <f64>::total_cmp)  
+            med1
         } else {
-            let (&med1, &med2) = evenmedian_by(&mut s, &mut <f64>::total_cmp);
             (med1 + med2) / 2.0
         }
     }
     /// Iterative weighted median with accuracy eps
-    fn medf_weighted(self, ws: Self, eps: f64) -> Result<f64, Me> { 
-        if self.len() != ws.len() { 
-            return merror("size","medf_weighted - data and weights lengths mismatch"); };
+    #[cfg(feature = "std")]
+    fn medf_weighted(self, ws: Self, eps: f64) -> Result<f64, Me> {
+        if self.len() != ws.len() {
+            return merror("size", "medf_weighted - data and weights lengths mismatch");
+        };
         if nans(self) {
-            return merror("Nan","medf_weighted - detected Nan in input"); };
+            return merror("nan", "medf_weighted - detected Nan in input");
+        };
         let weights_sum: f64 = ws.iter().sum();
-        let mut last_median  = 0_f64;
-        for (g,w) in self.iter().zip(ws) { last_median += w*g; }; 
-        last_median /= weights_sum; // start iterating from the weighted centre 
+        let mut last_median = 0_f64;
+        for (g, w) in self.iter().zip(ws) {
+            last_median += w * g;
+        }
+        last_median /= weights_sum; // start iterating from the weighted centre
         let mut last_recsum = 0f64;
-        loop { // iteration till accuracy eps is exceeded  
-            let mut median = 0_f64;   
+        loop {
+            // iteration till accuracy eps is exceeded
+            let mut median = 0_f64;
             let mut recsum = 0_f64;
-            for (x,w) in self.iter().zip(ws) {   
-                let mag = (x-last_median).abs(); 
-                if mag.is_normal() { // only use this point if its distance from median is > 0.0
-                    let rec = w/(mag.sqrt()); // weight/distance
-                    median += rec*x; 
-                    recsum += rec // add separately the reciprocals for final scaling   
-                } 
+            for (x, w) in self.iter().zip(ws) {
+                let mag = (x - last_median).abs();
+                if mag.is_normal() {
+                    // only use this point if its distance from median is > 0.0
+                    let rec = w / (mag.sqrt()); // weight/distance
+                    median += rec * x;
+                    recsum += rec // add separately the reciprocals for final scaling
+                }
             }
-            if recsum-last_recsum < eps { return Ok(median/recsum); };  // termination test 
-            last_median = median/recsum;
-            last_recsum = recsum;            
+            if recsum - last_recsum < eps {
+                return Ok(median / recsum);
+            }; // termination test
+            last_median = median / recsum;
+            last_recsum = recsum;
         }
     }
     /// Zero mean/median data produced by subtracting the centre,
@@ -128,6 +301,7 @@ impl Medianf64 for &[f64] {
     }
     /// Median correlation = cosine of an angle between two zero median vectors,
     /// (where the two data samples are interpreted as n-dimensional vectors).
+    #[cfg(feature = "std")]
     fn medf_correlation(self, v: Self) -> Result<f64, Me> {
         let mut sx2 = 0_f64;
         let mut sy2 = 0_f64;
@@ -146,7 +320,7 @@ impl Medianf64 for &[f64] {
             .sum();
         let res = sxy / (sx2 * sy2).sqrt();
         if res.is_nan() {
-            merror("Nan", "medf_correlation: Nan result!")
+            merror("nan", "medf_correlation: Nan result!")
         } else {
             Ok(res)
         }
@@ -160,6 +334,263 @@ impl Medianf64 for &[f64] {
             .collect::<Vec<f64>>()
             .medf_unchecked()
     }
+    /// Item of rank `k` (0-based) in ascending order, i.e. the k-th order statistic.
+    /// Returns `size` error when `k` is out of range and `nan` error when any data item is a NaN.
+    fn select_kf(self, k: usize) -> Result<f64, Me> {
+        let n = self.len();
+        if k >= n {
+            return merror("size", format!("select_kf: rank {k} out of range 0..{n}"));
+        };
+        if nans(self) {
+            return merror("nan", "select_kf: Nan in input!");
+        };
+        Ok(medians_block(&mut self.to_vec(), k, k, &mut <f64>::total_cmp).0)
+    }
+    /// Quantile `p` in [0,1], defined by one of the nine Hyndman-Fan `method`s.
+    /// Returns `size` error when `p` is outside [0,1] and `nan` error when any data item is a NaN.
+    /// # Example
+    /// ```
+    /// use medians::{Medianf64,QuantileMethod};
+    /// let v = [1_f64,2.,3.,4.,5.,6.,7.,8.,9.,10.];
+    /// assert_eq!(v.quantile(0.25,QuantileMethod::Linear).unwrap(),3.25);
+    /// assert_eq!(v.quantile(0.25,QuantileMethod::Weibull).unwrap(),2.75);
+    /// assert_eq!(v.quantile(0.25,QuantileMethod::InvertedCdf).unwrap(),3.);
+    /// ```
+    fn quantile(self, p: f64, method: QuantileMethod) -> Result<f64, Me> {
+        Ok(self.quantiles(&[p], method)?[0])
+    }
+    /// Quantiles `ps`, each in [0,1], defined by one of the nine Hyndman-Fan `method`s.
+    /// All the required order statistics are found in a single descent.
+    fn quantiles(self, ps: &[f64], method: QuantileMethod) -> Result<Vec<f64>, Me> {
+        if nans(self) {
+            return merror("nan", "quantiles: Nan in input!");
+        };
+        self.qquantiles_by(ps, method, &mut <f64>::total_cmp, |&x| x)
+    }
+    /// Indices of the median(s), ordered as by `total_cmp`, found by binary partitioning
+    /// of the `RadixKey` keys. Ties are broken by the indices: of equal items,
+    /// the one with the lowest index ranks first. Returns `nan` error when any data item is a NaN.
+    /// # Example
+    /// ```
+    /// use medians::{Medianf64,ConstMedians};
+    /// let v = [3_f64,1.,2.,1.,3.,2.];
+    /// assert_eq!(v.argmedf().unwrap(),ConstMedians::Even((2,5)));
+    /// ```
+    fn argmedf(self) -> Result<ConstMedians<usize>, Me> {
+        if self.is_empty() {
+            return merror("size", "argmedf: zero length data");
+        };
+        if nans(self) {
+            return merror("nan", "argmedf: Nan in input!");
+        };
+        Ok(argmedian_keys(self.iter().map(|&x| x.to_key())))
+    }
+}
+
+/// Medians of &[f32], mirroring those of &[f64].
+impl Medianf32 for &[f32] {
+    /// Returns `nan` error when any data item is a NaN, otherwise the median
+    fn medf_checked(self) -> Result<f32, Me> {
+        let n = self.len();
+        if self.iter().any(|x| x.is_nan()) {
+            return merror("nan", "medf_checked: Nan in input!");
+        };
+        match n {
+            0 => return merror("size", "medf_checked: zero length data"),
+            1 => return Ok(self[0]),
+            2 => return Ok((self[0] + self[1]) / 2.0),
+            _ => (),
+        };
+        let (med1, med2) = if n <= NETWORK_MAX {
+            // few items, copied to the stack and selected by their median network
+            network_medians(self)
+        } else {
+            // the values themselves are selected, by the branch reduced block partitioning
+            medians_block(
+                &mut self.to_vec(),
+                (n - 1) / 2,
+                n / 2,
+                &mut <f32>::total_cmp,
+            )
+        };
+        if (n & 1) == 1 {
+            Ok(med1)
+        } else {
+            Ok((med1 + med2) / 2.0)
+        }
+    }
+
+    /// Median by binary partitioning of order preserving u32 keys of the data (see `RadixKey`),
+    /// rather than by comparisons. Returns `nan` error when any data item is a NaN, as `medf_checked`.
+    fn medf_radix(self) -> Result<f32, Me> {
+        let n = self.len();
+        if n == 0 {
+            return merror("size", "medf_radix: zero length data");
+        };
+        let mut keys = self
+            .iter()
+            .map(|&x| {
+                if x.is_nan() {
+                    merror("nan", "medf_radix: Nan in input!")
+                } else {
+                    Ok(x.to_key())
+                }
+            })
+            .collect::<Result<Vec<u32>, Me>>()?;
+        if (n & 1) == 1 {
+            Ok(f32::from_key(*oddmedian_bits(&mut keys)))
+        } else {
+            let (&k1, &k2) = evenmedian_bits(&mut keys);
+            Ok((f32::from_key(k1) + f32::from_key(k2)) / 2.0)
+        }
+    }
+
+    /// Use this when your data does not contain any NaNs.
+    /// NaNs will not raise an error. However, they will affect the result
+    /// because of their order positions beyond infinity.
+    fn medf_unchecked(self) -> f32 {
+        let n = self.len();
+        match n {
+            0 => return 0_f32,
+            1 => return self[0],
+            2 => return (self[0] + self[1]) / 2.0,
+            _ => (),
+        };
+        let (med1, med2) = if n <= NETWORK_MAX {
+            network_medians(self)
+        } else {
+            medians_block(
+                &mut self.to_vec(),
+                (n - 1) / 2,
+                n / 2,
+                &mut <f32>::total_cmp,
+            )
+        };
+        if (n & 1) == 1 {
+            med1
+        } else {
+            (med1 + med2) / 2.0
+        }
+    }
+    /// Iterative weighted median with accuracy eps
+    #[cfg(feature = "std")]
+    fn medf_weighted(self, ws: Self, eps: f32) -> Result<f32, Me> {
+        if self.len() != ws.len() {
+            return merror("size", "medf_weighted - data and weights lengths mismatch");
+        };
+        if self.iter().any(|x| x.is_nan()) {
+            return merror("nan", "medf_weighted - detected Nan in input");
+        };
+        let weights_sum: f32 = ws.iter().sum();
+        let mut last_median = 0_f32;
+        for (g, w) in self.iter().zip(ws) {
+            last_median += w * g;
+        }
+        last_median /= weights_sum; // start iterating from the weighted centre
+        let mut last_recsum = 0_f32;
+        loop {
+            // iteration till accuracy eps is exceeded
+            let mut median = 0_f32;
+            let mut recsum = 0_f32;
+            for (x, w) in self.iter().zip(ws) {
+                let mag = (x - last_median).abs();
+                if mag.is_normal() {
+                    // only use this point if its distance from median is > 0.0
+                    let rec = w / (mag.sqrt()); // weight/distance
+                    median += rec * x;
+                    recsum += rec // add separately the reciprocals for final scaling
+                }
+            }
+            if recsum - last_recsum < eps {
+                return Ok(median / recsum);
+            }; // termination test
+            last_median = median / recsum;
+            last_recsum = recsum;
+        }
+    }
+    /// Zero mean/median data produced by subtracting the centre,
+    /// typically the mean or the median.
+    fn medf_zeroed(self, centre: f32) -> Vec<f32> {
+        self.iter().map(|&s| s - centre).collect()
+    }
+    /// Median correlation = cosine of an angle between two zero median vectors,
+    /// (where the two data samples are interpreted as n-dimensional vectors).
+    #[cfg(feature = "std")]
+    fn medf_correlation(self, v: Self) -> Result<f32, Me> {
+        let mut sx2 = 0_f32;
+        let mut sy2 = 0_f32;
+        let smedian = self.medf_checked()?;
+        let vmedian = v.medf_checked()?;
+        let sxy: f32 = self
+            .iter()
+            .zip(v)
+            .map(|(&xt, &yt)| {
+                let x = xt - smedian;
+                let y = yt - vmedian;
+                sx2 += x * x;
+                sy2 += y * y;
+                x * y
+            })
+            .sum();
+        let res = sxy / (sx2 * sy2).sqrt();
+        if res.is_nan() {
+            merror("nan", "medf_correlation: Nan result!")
+        } else {
+            Ok(res)
+        }
+    }
+    /// Data dispersion estimator MAD (Median of Absolute Differences).
+    /// MAD is more stable than standard deviation and more general than quartiles.
+    /// When argument `centre` is the median, it is the most stable measure of data dispersion.
+    fn madf(self, centre: f32) -> f32 {
+        self.iter()
+            .map(|&s| (s - centre).abs())
+            .collect::<Vec<f32>>()
+            .medf_unchecked()
+    }
+    /// Item of rank `k` (0-based) in ascending order, i.e. the k-th order statistic.
+    /// Returns `size` error when `k` is out of range and `nan` error when any data item is a NaN.
+    fn select_kf(self, k: usize) -> Result<f32, Me> {
+        let n = self.len();
+        if k >= n {
+            return merror("size", format!("select_kf: rank {k} out of range 0..{n}"));
+        };
+        if self.iter().any(|x| x.is_nan()) {
+            return merror("nan", "select_kf: Nan in input!");
+        };
+        Ok(medians_block(&mut self.to_vec(), k, k, &mut <f32>::total_cmp).0)
+    }
+    /// Quantile `p` in [0,1], defined by one of the nine Hyndman-Fan `method`s.
+    /// Returns `size` error when `p` is outside [0,1] and `nan` error when any data item is a NaN.
+    fn quantile(self, p: f64, method: QuantileMethod) -> Result<f32, Me> {
+        Ok(self.quantiles(&[p], method)?[0])
+    }
+    /// Quantiles `ps`, each in [0,1], defined by one of the nine Hyndman-Fan `method`s.
+    /// All the required order statistics are found in a single descent.
+    fn quantiles(self, ps: &[f64], method: QuantileMethod) -> Result<Vec<f32>, Me> {
+        if self.iter().any(|x| x.is_nan()) {
+            return merror("nan", "quantiles: Nan in input!");
+        };
+        // interpolated in f64 for accuracy
+        Ok(self
+            .qquantiles_by(ps, method, &mut <f32>::total_cmp, |&x| x as f64)?
+            .iter()
+            .map(|&x| x as f32)
+            .collect())
+    }
+
+    /// Indices of the median(s), ordered as by `total_cmp`, found by binary partitioning
+    /// of the `RadixKey` keys. Ties are broken by the indices.
+    /// Returns `nan` error when any data item is a NaN.
+    fn argmedf(self) -> Result<ConstMedians<usize>, Me> {
+        if self.is_empty() {
+            return merror("size", "argmedf: zero length data");
+        };
+        if self.iter().any(|x| x.is_nan()) {
+            return merror("nan", "argmedf: Nan in input!");
+        };
+        Ok(argmedian_keys(self.iter().map(|&x| x.to_key() as u64)))
+    }
 }
 
 /// Medians of &[T]
@@ -174,6 +605,17 @@ impl<'a, T> Median<'a, T> for &'a [T] {
         self,
         c: &mut impl FnMut(&T, &T) -> Ordering,
         q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me> {
+        self.qmedian_by_with(SelectStrategy::MidOf3, c, q)
+    }
+
+    /// Median of `&[T]` by comparison `c`, quantified to a single f64 by `q`,
+    /// with pivots chosen by `strategy`. Allows comparing the strategies on particular data.
+    fn qmedian_by_with(
+        self,
+        strategy: SelectStrategy,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
     ) -> Result<f64, Me> {
         let n = self.len();
         match n {
@@ -182,56 +624,368 @@ impl<'a, T> Median<'a, T> for &'a [T] {
             2 => return Ok((q(&self[0]) + q(&self[1])) / 2.0),
             _ => (),
         };
-        let mut s = self.ref_vec(0..self.len());
+        let mut s = self.iter().collect::<Vec<&T>>();
         if (n & 1) == 1 {
-            Ok(q(oddmedian_by(&mut s, c)))
+            Ok(q(select_with(&mut s, n / 2, strategy, c)))
         } else {
-            let (med1, med2) = evenmedian_by(&mut s, c);
+            let (med1, med2) = evenmedian_with(&mut s, strategy, c);
             Ok((q(med1) + q(med2)) / 2.0)
         }
     }
 
-    /// Median of `&[T]`, quantifiable to u64's by `q`. 
+    /// Median of `&[T]`, quantifiable to u64's by `q`.
     /// Returns a single f64, possibly losing some precision for even medians.
     /// When T is a primitive type directly convertible to u64, use `as u64` for `q`.
     /// When u64:From<T> is implemented, use `|x| x.into()` as `q`.
     /// In all other cases, use custom quantification closure `q`.
     /// When T is not quantifiable at all, use the ultimate `median_by` method.
-    fn uqmedian(
-        self,
-        q: impl Fn(&T) -> u64,
-    ) -> Result<f64, Me> {
+    fn uqmedian(self, q: impl Fn(&T) -> u64) -> Result<f64, Me> {
         let n = self.len();
         match n {
             0 => return merror("size", "uqmedian_by: zero length data"),
             1 => return Ok(q(&self[0]) as f64),
-            2 => return Ok( (q(&self[0]) as f64 + q(&self[1]) as f64) / 2.0 ),
+            2 => return Ok((q(&self[0]) as f64 + q(&self[1]) as f64) / 2.0),
             _ => (),
         };
-        let mut s:Vec<u64> = self.iter().map(q).collect();
+        let mut s: Vec<u64> = self.iter().map(q).collect();
         match medianu64(&mut s)? {
             Medians::Odd(r) => Ok(*r as f64),
-            Medians::Even((r1, r2)) => Ok((*r1 as f64 + *r2 as f64) / 2_f64)
+            Medians::Even((r1, r2)) => Ok((*r1 as f64 + *r2 as f64) / 2_f64),
         }
     }
 
-    /// Median(s) of unquantifiable type by general comparison closure
+    /// Median(s) of unquantifiable type by general comparison closure.
+    /// Up to `NETWORK_MAX` items are selected by their median network,
+    /// over references held on the stack.
     fn median_by(self, c: &mut impl FnMut(&T, &T) -> Ordering) -> Result<Medians<'a, T>, Me> {
+        let n = self.len();
+        if n == 0 || n > NETWORK_MAX {
+            return self.median_by_with(SelectStrategy::MidOf3, c);
+        };
+        let mut a = [&self[0]; NETWORK_MAX];
+        for (r, x) in a.iter_mut().zip(self) {
+            *r = x;
+        }
+        let (m1, m2) = median_network(&mut a[..n], &mut |a: &&T, b: &&T| c(a, b));
+        if (n & 1) == 1 {
+            Ok(Medians::Odd(m1))
+        } else {
+            Ok(Medians::Even((m1, m2)))
+        }
+    }
+
+    /// Median(s) by the order of keys extracted by `f`, mirroring `sort_by_key` of slices.
+    /// The keys are extracted anew for each comparison, so `f` should be cheap.
+    fn median_by_key<K: Ord>(self, mut f: impl FnMut(&T) -> K) -> Result<Medians<'a, T>, Me> {
+        self.median_by(&mut |a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Median(s) by the order of keys extracted by `f`, mirroring `sort_by_cached_key` of slices.
+    /// Each key is computed only once, into a buffer of (key, index) pairs,
+    /// within which the selection then proceeds. Better when `f` is costly.
+    /// # Example
+    /// ```
+    /// use medians::{Median,Medians};
+    /// let v = ["three","one","eleven","four","seven"];
+    /// let Ok(Medians::Odd(&m)) = v.median_by_cached_key(|w| w.len())
+    ///     else { panic!("odd median expected") };
+    /// assert_eq!(m,"three");
+    /// ```
+    fn median_by_cached_key<K: Ord>(
+        self,
+        mut f: impl FnMut(&T) -> K,
+    ) -> Result<Medians<'a, T>, Me> {
+        let n = self.len();
+        if n == 0 {
+            return merror("size", "median_by_cached_key: zero length data");
+        };
+        let need = (n - 1) / 2; // lower median position
+        let mut keyed: Vec<(K, usize)> = self.iter().enumerate().map(|(i, x)| (f(x), i)).collect();
+        if (n & 1) == 1 {
+            select_mut(
+                &mut keyed,
+                need,
+                SelectStrategy::MidOf3,
+                &mut <(K, usize)>::cmp,
+            );
+            Ok(Medians::Odd(&self[keyed[need].1]))
+        } else {
+            multiselect_mut(
+                &mut keyed,
+                &[need, need + 1],
+                SelectStrategy::MidOf3,
+                &mut <(K, usize)>::cmp,
+            );
+            Ok(Medians::Even((
+                &self[keyed[need].1],
+                &self[keyed[need + 1].1],
+            )))
+        }
+    }
+
+    /// Median(s) of `Ord` data, by their natural order
+    fn median(self) -> Result<Medians<'a, T>, Me>
+    where
+        T: Ord,
+    {
+        self.median_by(&mut <T>::cmp)
+    }
+
+    /// Median(s) by general comparison closure `c`, also returning the `SelectStats`
+    /// of the selection: comparator calls, swaps, partition passes and the range
+    /// remaining in play after each pass. Useful for comparing comparators and data layouts.
+    /// # Example
+    /// ```
+    /// use medians::{Median,Medians};
+    /// let v = [5_u64,9,3,7,1,8,2];
+    /// let (Medians::Odd(&m),st) = v.median_by_stats(&mut <u64>::cmp).unwrap()
+    ///     else { panic!("odd median expected") };
+    /// assert_eq!(m,5);
+    /// assert_eq!(st.passes,st.remaining.len());
+    /// assert!(st.comparisons >= v.len()-1);
+    /// ```
+    fn median_by_stats(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<(Medians<'a, T>, SelectStats), Me> {
+        let n = self.len();
+        let mut st = SelectStats::default();
+        match n {
+            0 => return merror("size", "median_by_stats: zero length data"),
+            1 => return Ok((Medians::Odd(&self[0]), st)),
+            _ => (),
+        };
+        let mut cref = |a: &&T, b: &&T| c(a, b);
+        if n <= NETWORK_MAX {
+            // the same median network as `median_by`
+            let mut a = [&self[0]; NETWORK_MAX];
+            for (r, x) in a.iter_mut().zip(self) {
+                *r = x;
+            }
+            let (m1, m2) = median_network_recorded(&mut a[..n], &mut cref, &mut st);
+            return if (n & 1) == 1 {
+                Ok((Medians::Odd(m1), st))
+            } else {
+                Ok((Medians::Even((m1, m2)), st))
+            };
+        };
+        let mut s = self.iter().collect::<Vec<&T>>();
+        if (n & 1) == 1 {
+            multiselect_recorded(&mut s, &[n / 2], &mut cref, &mut st);
+            Ok((Medians::Odd(s[n / 2]), st))
+        } else {
+            multiselect_recorded(&mut s, &[n / 2 - 1, n / 2], &mut cref, &mut st);
+            Ok((Medians::Even((s[n / 2 - 1], s[n / 2])), st))
+        }
+    }
+
+    /// Median(s) by general comparison closure `c`, with pivots chosen by `strategy`.
+    /// Allows comparing the strategies on particular data.
+    /// # Example
+    /// ```
+    /// use medians::{Median,Medians,SelectStrategy};
+    /// let v = [5_u64,9,3,7,1,8,2];
+    /// let Ok(Medians::Odd(&m)) = v.median_by_with(SelectStrategy::FloydRivest,&mut <u64>::cmp)
+    ///     else { panic!("odd median expected") };
+    /// assert_eq!(m,5);
+    /// ```
+    fn median_by_with(
+        self,
+        strategy: SelectStrategy,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Medians<'a, T>, Me> {
         let n = self.len();
         match n {
             0 => return merror("size", "median_ord: zero length data"),
             1 => return Ok(Medians::Odd(&self[0])),
-            2 => return Ok(Medians::Even((&self[0], &self[1]))),
             _ => (),
         };
-        let mut s = self.ref_vec(0..self.len());
+        let mut s = self.iter().collect::<Vec<&T>>();
         if (n & 1) == 1 {
-            Ok(Medians::Odd(oddmedian_by(&mut s, c)))
+            Ok(Medians::Odd(select_with(&mut s, n / 2, strategy, c)))
         } else {
-            Ok(Medians::Even(evenmedian_by(&mut s, c)))
+            Ok(Medians::Even(evenmedian_with(&mut s, strategy, c)))
         }
     }
 
+    /// Item of rank `k` (0-based) by general comparison closure `c`.
+    /// For example, `k = 0` is the minimum and `k = self.len()-1` is the maximum.
+    /// Returns `size` error when `k` is out of range.
+    fn select_k_by(self, k: usize, c: &mut impl FnMut(&T, &T) -> Ordering) -> Result<&'a T, Me> {
+        let n = self.len();
+        if k >= n {
+            return merror("size", format!("select_k_by: rank {k} out of range 0..{n}"));
+        };
+        let mut s = self.iter().collect::<Vec<&T>>();
+        Ok(select_by(&mut s, k, c))
+    }
+
+    /// Item of rank `k` (0-based) by comparison `c`, quantified to a single f64 by `q`.
+    fn qselect_k_by(
+        self,
+        k: usize,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me> {
+        Ok(q(self.select_k_by(k, c)?))
+    }
+
+    /// Index of the item of rank `k` (0-based) by comparison `c`, into `self`.
+    /// Ties are broken by the indices: of equal items, the one with the lowest index ranks first.
+    /// Returns `size` error when `k` is out of range.
+    fn argselect_k_by(self, k: usize, c: &mut impl FnMut(&T, &T) -> Ordering) -> Result<usize, Me> {
+        let n = self.len();
+        if k >= n {
+            return merror(
+                "size",
+                format!("argselect_k_by: rank {k} out of range 0..{n}"),
+            );
+        };
+        Ok(argselect_by(self, k, c))
+    }
+
+    /// Index/indices of the median(s) by comparison `c`, into `self`, e.g. to recover
+    /// the records that the medians came from. Ties are broken by the indices,
+    /// so the result is deterministic.
+    /// # Example
+    /// ```
+    /// use medians::{Median,ConstMedians};
+    /// let v = ["b","a","c","a","b"];
+    /// assert_eq!(v.argmedian_by(&mut |a,b| a.cmp(b)).unwrap(),ConstMedians::Odd(0));
+    /// ```
+    fn argmedian_by(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<ConstMedians<usize>, Me> {
+        if self.is_empty() {
+            return merror("size", "argmedian_by: zero length data");
+        };
+        Ok(argmedian_by(self, c))
+    }
+
+    /// The `k` smallest items by comparison `c`, in ascending order, i.e. `partial_sort_by(0..k,c)`.
+    /// Selects the item of rank `k-1` first, then sorts only the items below it.
+    /// Returns `size` error when `k` exceeds the data length.
+    fn smallest_k_by(
+        self,
+        k: usize,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Vec<&'a T>, Me> {
+        self.partial_sort_by(0..k, c)
+    }
+
+    /// The `k` largest items by comparison `c`, in descending order (the largest first).
+    /// Returns `size` error when `k` exceeds the data length.
+    /// # Example
+    /// ```
+    /// use medians::Median;
+    /// let v = [5_u64,9,3,7,1,8,2];
+    /// assert_eq!(v.largest_k_by(3,&mut <u64>::cmp).unwrap(),[&9,&8,&7]);
+    /// ```
+    fn largest_k_by(
+        self,
+        k: usize,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Vec<&'a T>, Me> {
+        self.partial_sort_by(0..k, &mut |a, b| c(b, a))
+    }
+
+    /// Items of the ranks (0-based) within `rng` by comparison `c`, in ascending order,
+    /// as they would appear in `rng` of the fully sorted data. Only the first and the last
+    /// ranks of `rng` are selected, then just the items between them are sorted.
+    /// Returns `size` error when `rng` is not within the data.
+    /// # Example
+    /// ```
+    /// use medians::Median;
+    /// let v = [5_u64,9,3,7,1,8,2];
+    /// assert_eq!(v.partial_sort_by(2..5,&mut <u64>::cmp).unwrap(),[&3,&5,&7]);
+    /// ```
+    fn partial_sort_by(
+        self,
+        rng: core::ops::Range<usize>,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Vec<&'a T>, Me> {
+        let n = self.len();
+        if rng.start > rng.end || rng.end > n {
+            return merror(
+                "size",
+                format!("partial_sort_by: range {rng:?} not within 0..{n}"),
+            );
+        };
+        let mut s = self.iter().collect::<Vec<&T>>();
+        partial_sort_mut(&mut s, rng.clone(), &mut |a: &&T, b: &&T| c(a, b));
+        Ok(s[rng].to_vec())
+    }
+
+    /// Items of all ranks `ks` (0-based, in any order) by comparison `c`,
+    /// e.g. all the deciles. They are found in a single recursive descent,
+    /// which is faster than selecting each rank separately.
+    /// Returns `size` error when any rank is out of range.
+    fn select_many_by(
+        self,
+        ks: &[usize],
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Vec<&'a T>, Me> {
+        let n = self.len();
+        let sorted = sorted_ranks(ks, n)?;
+        let mut s = self.iter().collect::<Vec<&T>>();
+        let mut res = Vec::with_capacity(sorted.len());
+        multiselect_by(&mut s, 0..n, &sorted, &mut res, c);
+        Ok(ks
+            .iter()
+            .map(|&k| res[sorted.partition_point(|&j| j < k)])
+            .collect())
+    }
+
+    /// Quantile `p` in [0,1] of `&[T]` by comparison `c`, defined by one of the nine
+    /// Hyndman-Fan `method`s. The order statistics are quantified to f64 by `q` for interpolation.
+    fn qquantile_by(
+        self,
+        p: f64,
+        method: QuantileMethod,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me> {
+        Ok(self.qquantiles_by(&[p], method, c, q)?[0])
+    }
+
+    /// Quantiles `ps` of `&[T]` by comparison `c`, quantified to f64 by `q`.
+    /// All the required order statistics are found in a single descent.
+    /// Returns `size` error for empty data or when any `p` is outside [0,1].
+    fn qquantiles_by(
+        self,
+        ps: &[f64],
+        method: QuantileMethod,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<Vec<f64>, Me> {
+        let n = self.len();
+        if n == 0 {
+            return merror("size", "qquantiles_by: zero length data");
+        };
+        if let Some(p) = ps.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+            return merror("size", format!("qquantiles_by: {p} is outside [0,1]"));
+        };
+        let ranks: Vec<(usize, usize, f64)> =
+            ps.iter().map(|&p| quantile_ranks(p, n, method)).collect();
+        let ks: Vec<usize> = ranks.iter().flat_map(|&(lo, hi, _)| [lo, hi]).collect();
+        let items = self.select_many_by(&ks, c)?;
+        Ok(ranks
+            .iter()
+            .zip(items.chunks(2))
+            .map(|(&(_, _, w), pair)| {
+                // avoid multiplying infinities by zero weights
+                if w == 0.0 {
+                    q(pair[0])
+                } else if w == 1.0 {
+                    q(pair[1])
+                } else {
+                    (1.0 - w) * q(pair[0]) + w * q(pair[1])
+                }
+            })
+            .collect())
+    }
+
     /// Zero mean/median data produced by subtracting the centre
     fn zeroed(self, centre: f64, q: impl Fn(&T) -> f64) -> Result<Vec<f64>, Me> {
         Ok(self.iter().map(|s| q(s) - centre).collect())
@@ -248,6 +1002,7 @@ impl<'a, T> Median<'a, T> for &'a [T] {
     /// assert_eq!(v1.medf_correlation(&v2).unwrap(),-0.1076923076923077);
     /// assert_eq!(v1.med_correlation(&v2,&mut |a,b| a.total_cmp(b),|&a| identity(a)).unwrap(),-0.1076923076923077);
     /// ```
+    #[cfg(feature = "std")]
     fn med_correlation(
         self,
         v: Self,
@@ -271,7 +1026,7 @@ impl<'a, T> Median<'a, T> for &'a [T] {
             .sum();
         let res = sxy / (sx2 * sy2).sqrt();
         if res.is_nan() {
-            merror("Nan", "correlation: Nan result!")
+            merror("nan", "correlation: Nan result!")
         } else {
             Ok(res)
         }
@@ -286,3 +1041,172 @@ impl<'a, T> Median<'a, T> for &'a [T] {
             .medf_unchecked()
     }
 }
+
+/// Medians of &mut [T], found in place
+impl<'a, T> MedianMut<'a, T> for &'a mut [T] {
+    /// Median(s) by general comparison closure `c`, without allocation.
+    /// Reorders the data: afterwards the (lower) median is at subscript `(n-1)/2`,
+    /// with no greater items before it and no lesser items after it.
+    /// # Example
+    /// ```
+    /// use medians::{MedianMut,Medians};
+    /// let mut v = [5_u64,9,3,7,1,8,2,4];
+    /// let Ok(Medians::Even((&m1,&m2))) = v.median_in_place_by(&mut <u64>::cmp)
+    ///     else { panic!("even medians expected") };
+    /// assert_eq!((m1,m2),(4,5));
+    /// assert_eq!(v[3],4);
+    /// ```
+    fn median_in_place_by(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Medians<'a, T>, Me> {
+        let n = self.len();
+        if n == 0 {
+            return merror("size", "median_in_place_by: zero length data");
+        };
+        let need = (n - 1) / 2; // lower median position
+        if (n & 1) == 1 {
+            select_mut(self, need, SelectStrategy::MidOf3, c);
+            Ok(Medians::Odd(&self[need]))
+        } else {
+            multiselect_mut(self, &[need, need + 1], SelectStrategy::MidOf3, c);
+            Ok(Medians::Even((&self[need], &self[need + 1])))
+        }
+    }
+
+    /// Median by comparison `c`, quantified to a single f64 by `q`, without allocation.
+    /// Reorders the data.
+    fn qmedian_in_place_by(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me> {
+        match self.median_in_place_by(c)? {
+            Medians::Odd(m) => Ok(q(m)),
+            Medians::Even((m1, m2)) => Ok((q(m1) + q(m2)) / 2.0),
+        }
+    }
+
+    /// Splits the data in place around the item of rank `k` (0-based) by comparison `c`,
+    /// like C++ `nth_element`, but reporting the run of the items equal to it explicitly.
+    /// Returns the three sub-slices, the selected item is at the start of `equal`.
+    /// Returns `size` error when `k` is out of range.
+    /// # Example
+    /// ```
+    /// use medians::MedianMut;
+    /// let mut v = [5_u64,9,3,7,1,7,2];
+    /// let p = v.partition_at_k_by(4,&mut <u64>::cmp).unwrap();
+    /// assert_eq!(*p.item(),7);
+    /// assert_eq!((p.less.len(),p.equal.len(),p.greater.len()),(4,2,1));
+    /// ```
+    fn partition_at_k_by(
+        self,
+        k: usize,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Partitioned<'a, T>, Me> {
+        let n = self.len();
+        if k >= n {
+            return merror(
+                "size",
+                format!("partition_at_k_by: rank {k} out of range 0..{n}"),
+            );
+        };
+        select_mut(self, k, SelectStrategy::MidOf3, c);
+        let (eqsub, gtsub) = equal_run(self, k, c);
+        let (less, rest) = self.split_at_mut(eqsub);
+        let (equal, greater) = rest.split_at_mut(gtsub - eqsub);
+        Ok(Partitioned {
+            less,
+            equal,
+            greater,
+        })
+    }
+
+    /// Splits the data in place around its (lower) median by comparison `c`,
+    /// e.g. for balanced bisection or for building kd-trees.
+    /// Returns `size` error for empty data.
+    fn partition_at_median_by(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Partitioned<'a, T>, Me> {
+        if self.is_empty() {
+            return merror("size", "partition_at_median_by: zero length data");
+        };
+        let need = (self.len() - 1) / 2;
+        self.partition_at_k_by(need, c)
+    }
+}
+
+/// Medians of all iterators
+impl<I: IntoIterator> MedianIter for I {
+    /// Median(s) by general comparison closure `c`, moved out of the internal buffer.
+    /// # Example
+    /// ```
+    /// use medians::{MedianIter,ConstMedians};
+    /// let words = "the quick brown fox jumps".split(' ');
+    /// assert_eq!(words.iter_median_by(&mut |a,b| a.cmp(b)).unwrap(),ConstMedians::Odd("jumps"));
+    /// ```
+    fn iter_median_by(
+        self,
+        c: &mut impl FnMut(&Self::Item, &Self::Item) -> Ordering,
+    ) -> Result<ConstMedians<Self::Item>, Me> {
+        let mut s: Vec<Self::Item> = self.into_iter().collect();
+        let n = s.len();
+        if n == 0 {
+            return merror("size", "iter_median_by: empty iterator");
+        };
+        let need = (n - 1) / 2; // lower median position
+        if (n & 1) == 1 {
+            select_mut(&mut s, need, SelectStrategy::MidOf3, c);
+            Ok(ConstMedians::Odd(s.swap_remove(need)))
+        } else {
+            multiselect_mut(&mut s, &[need, need + 1], SelectStrategy::MidOf3, c);
+            // removing the upper median first leaves the lower one in place
+            let med2 = s.swap_remove(need + 1);
+            Ok(ConstMedians::Even((s.swap_remove(need), med2)))
+        }
+    }
+
+    /// Median of f64s, by binary partitioning of their `RadixKey` keys, collected into the buffer.
+    /// Returns `nan` error when any item is a NaN.
+    fn iter_medf(self) -> Result<f64, Me>
+    where
+        Self: IntoIterator<Item = f64>,
+    {
+        let mut keys = self
+            .into_iter()
+            .map(|x| {
+                if x.is_nan() {
+                    merror("nan", "iter_medf: Nan in input!")
+                } else {
+                    Ok(x.to_key())
+                }
+            })
+            .collect::<Result<Vec<u64>, Me>>()?;
+        let n = keys.len();
+        if n == 0 {
+            return merror("size", "iter_medf: empty iterator");
+        };
+        if (n & 1) == 1 {
+            Ok(f64::from_key(*oddmedian_bits(&mut keys)))
+        } else {
+            let (&k1, &k2) = evenmedian_bits(&mut keys);
+            Ok((f64::from_key(k1) + f64::from_key(k2)) / 2.0)
+        }
+    }
+
+    /// Median(s) of u64s, by binary partitioning of the collected buffer
+    fn iter_medianu64(self) -> Result<ConstMedians<u64>, Me>
+    where
+        Self: IntoIterator<Item = u64>,
+    {
+        let mut s: Vec<u64> = self.into_iter().collect();
+        if s.is_empty() {
+            return merror("size", "iter_medianu64: empty iterator");
+        };
+        Ok(match medianu64(&mut s)? {
+            Medians::Odd(&m) => ConstMedians::Odd(m),
+            Medians::Even((&m1, &m2)) => ConstMedians::Even((m1, m2)),
+        })
+    }
+}
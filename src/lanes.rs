@@ -0,0 +1,217 @@
+use alloc::{format, vec::Vec};
+use core::cmp::Ordering;
+
+use crate::algos::{evenmedian_by, oddmedian_by};
+use crate::{merror, Me, MedianMut};
+
+/// Strided view of a slice: `len` items from `start`, `stride` apart.
+/// For example, a column of a row-major matrix of `cols` columns is
+/// `Strided::new(&data, col, cols, rows)`.
+#[derive(Clone, Copy, Debug)]
+pub struct Strided<'a, T> {
+    data: &'a [T],
+    start: usize,
+    stride: usize,
+    len: usize,
+}
+
+impl<'a, T> Strided<'a, T> {
+    /// New strided view, returns `size` error when it is empty or does not fit within `data`
+    pub fn new(data: &'a [T], start: usize, stride: usize, len: usize) -> Result<Self, Me> {
+        if len == 0 || stride == 0 {
+            return merror("size", "Strided::new: zero length or stride");
+        };
+        let last = (len - 1)
+            .checked_mul(stride)
+            .and_then(|span| span.checked_add(start));
+        if last.is_none_or(|last| last >= data.len()) {
+            return merror(
+                "size",
+                format!(
+                    "Strided::new: view {start}+{len}*{stride} overruns data length {}",
+                    data.len()
+                ),
+            );
+        };
+        Ok(Strided {
+            data,
+            start,
+            stride,
+            len,
+        })
+    }
+
+    /// Number of items in the view
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Always false, as empty views can not be constructed
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterator over the items of the view
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        self.data[self.start..]
+            .iter()
+            .step_by(self.stride)
+            .take(self.len)
+    }
+
+    /// Median by comparison `c`, quantified to a single f64 by `q`
+    pub fn qmedian_by(
+        &self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me> {
+        qmedian_scratch(&mut Vec::with_capacity(self.len), self.iter(), c, &q)
+    }
+}
+
+impl Strided<'_, f64> {
+    /// Median of the f64s in the view, NaNs raise an error
+    pub fn medf_checked(&self) -> Result<f64, Me> {
+        medf_scratch(&mut Vec::with_capacity(self.len), self.iter())
+    }
+
+    /// Median of absolute differences (MAD) from `centre`
+    pub fn madf(&self, centre: f64) -> f64 {
+        madf_scratch(&mut Vec::with_capacity(self.len), self.iter(), centre)
+    }
+}
+
+/// Median of f64s, collected into `scratch` and selected there in place
+fn medf_scratch<'a>(
+    scratch: &mut Vec<f64>,
+    lane: impl Iterator<Item = &'a f64>,
+) -> Result<f64, Me> {
+    scratch.clear();
+    for &x in lane {
+        if x.is_nan() {
+            return merror("nan", "medf_checked: Nan in input!");
+        };
+        scratch.push(x);
+    }
+    scratch.qmedian_in_place_by(&mut <f64>::total_cmp, |&x| x)
+}
+
+/// Median of absolute differences from `centre`, collected into `scratch`
+fn madf_scratch<'a>(
+    scratch: &mut Vec<f64>,
+    lane: impl Iterator<Item = &'a f64>,
+    centre: f64,
+) -> f64 {
+    scratch.clear();
+    scratch.extend(lane.map(|&x| (x - centre).abs()));
+    scratch
+        .qmedian_in_place_by(&mut <f64>::total_cmp, |&x| x)
+        .unwrap_or(0_f64)
+}
+
+/// Median by comparison `c`, quantified by `q`, of references collected into `scratch`
+fn qmedian_scratch<'a, T>(
+    scratch: &mut Vec<&'a T>,
+    lane: impl Iterator<Item = &'a T>,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+    q: &impl Fn(&T) -> f64,
+) -> Result<f64, Me> {
+    scratch.clear();
+    scratch.extend(lane);
+    match scratch.len() {
+        0 => merror("size", "qmedian_by: zero length data"),
+        n if (n & 1) == 1 => Ok(q(oddmedian_by(scratch, c))),
+        _ => {
+            let (med1, med2) = evenmedian_by(scratch, c);
+            Ok((q(med1) + q(med2)) / 2.0)
+        }
+    }
+}
+
+/// Strided views of all the lanes along `axis` of a row-major buffer `data` of `shape`,
+/// in the row-major order of the remaining axes.
+/// Returns `size` error when `shape` does not match `data` or `axis` is out of range.
+pub fn lanes<'a, T>(
+    data: &'a [T],
+    shape: &[usize],
+    axis: usize,
+) -> Result<impl Iterator<Item = Strided<'a, T>>, Me> {
+    if axis >= shape.len() {
+        return merror(
+            "size",
+            format!("lanes: axis {axis} out of range 0..{}", shape.len()),
+        );
+    };
+    let total = shape
+        .iter()
+        .try_fold(1_usize, |prod, &dim| prod.checked_mul(dim));
+    if total != Some(data.len()) || data.is_empty() {
+        return merror(
+            "size",
+            format!(
+                "lanes: shape {shape:?} does not match data length {}",
+                data.len()
+            ),
+        );
+    };
+    let len = shape[axis];
+    let stride: usize = shape[axis + 1..].iter().product();
+    let outer = data.len() / (len * stride);
+    Ok((0..outer).flat_map(move |o| {
+        (0..stride).map(move |i| Strided {
+            data,
+            start: o * len * stride + i,
+            stride,
+            len,
+        })
+    }))
+}
+
+/// Medians of all the lanes along `axis` of f64 `data`, row-major of `shape`.
+/// For example, `axis = 0` of a matrix gives the medians of its columns.
+/// One scratch buffer serves all the lanes. NaNs raise an error.
+pub fn medf_axis(data: &[f64], shape: &[usize], axis: usize) -> Result<Vec<f64>, Me> {
+    let lanes = lanes(data, shape, axis)?;
+    let mut scratch = Vec::with_capacity(shape[axis]);
+    lanes
+        .map(|lane| medf_scratch(&mut scratch, lane.iter()))
+        .collect()
+}
+
+/// MADs of all the lanes along `axis` of f64 `data`, row-major of `shape`,
+/// from their respective `centres`, e.g. as found by `medf_axis`.
+pub fn madf_axis(
+    data: &[f64],
+    shape: &[usize],
+    axis: usize,
+    centres: &[f64],
+) -> Result<Vec<f64>, Me> {
+    let lanes = lanes(data, shape, axis)?;
+    if centres.len() != data.len() / shape[axis] {
+        return merror(
+            "size",
+            "madf_axis: number of centres does not match the lanes",
+        );
+    };
+    let mut scratch = Vec::with_capacity(shape[axis]);
+    Ok(lanes
+        .zip(centres)
+        .map(|(lane, &centre)| madf_scratch(&mut scratch, lane.iter(), centre))
+        .collect())
+}
+
+/// Medians of all the lanes along `axis` of generic `data`, row-major of `shape`,
+/// by comparison `c`, quantified to f64 by `q`. One scratch buffer of references serves all the lanes.
+pub fn qmedian_axis_by<T>(
+    data: &[T],
+    shape: &[usize],
+    axis: usize,
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+    q: impl Fn(&T) -> f64,
+) -> Result<Vec<f64>, Me> {
+    let lanes = lanes(data, shape, axis)?;
+    let mut scratch = Vec::with_capacity(shape[axis]);
+    lanes
+        .map(|lane| qmedian_scratch(&mut scratch, lane.iter(), c, &q))
+        .collect()
+}
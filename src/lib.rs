@@ -4,16 +4,34 @@
 //! Fast new algorithms for computing medians of
 //! (one dimensional) vectors
 //!
+//! The default feature `std` can be turned off for `no_std` targets, which then need only `alloc`.
+//! The in-place selections, e.g. `medianu64` and trait `MedianMut`, do not allocate at all.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 /// Functions for finding medians
 pub mod algos;
 /// Methods that implement Display and traits
 pub mod implementations;
+/// Medians of strided views and along axes of row-major buffers
+pub mod lanes;
+/// Multithreaded medians of very large data
+#[cfg(feature = "std")]
+pub mod parallel;
+/// Reusable scratch buffers for allocation-free repeated medians
+pub mod workspace;
 
+use alloc::{string::String, vec::Vec};
 use core::cmp::Ordering;
 use core::fmt::Debug;
 
-use crate::algos::{evenmedianu64, evenmedianu8, midof3, oddmedianu64, oddmedianu8, oddmedu64};
+use crate::algos::{
+    argmedian_keys, diffbits, evenmedian_bits, evenmedian_bits_recorded, evenmedianu64,
+    evenmedianu8, median_network, multiselect_bits, oddmedian_bits, oddmedian_bits_recorded,
+    oddmedianu64, oddmedianu8, radixselu64, radixselu64_recorded, sorted_ranks, KeyBits,
+    NETWORK_MAX,
+};
 
 /// Shorthand type for medians errors with message payload specialized to String
 pub type Me = MedError<String>;
@@ -48,26 +66,167 @@ pub enum Medians<'a, T> {
     Even((&'a T, &'a T)),
 }
 
-/// Medians of u8 end type by fast radix search
+/// Enum for results of odd/even medians of simple (copyable) endtypes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstMedians<T> {
+    /// Odd sized data results in a single median
+    Odd(T),
+    /// Even sized data results in a pair of (centered) medians
+    Even((T, T)),
+}
+
+/// The nine quantile definitions of Hyndman and Fan (1996), as used by R (types 1-9) and NumPy.
+/// `Linear` (type 7) is the default of both R and NumPy.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QuantileMethod {
+    /// Type 1: inverse of the empirical distribution function
+    InvertedCdf,
+    /// Type 2: as type 1, averaging at discontinuities
+    AveragedInvertedCdf,
+    /// Type 3: nearest even order statistic (SAS definition)
+    ClosestObservation,
+    /// Type 4: linear interpolation of the empirical distribution function
+    InterpolatedInvertedCdf,
+    /// Type 5: piecewise linear, with knots midway through the steps
+    Hazen,
+    /// Type 6: linear interpolation of the expectations of order statistics
+    Weibull,
+    /// Type 7: linear interpolation of the modes of order statistics
+    #[default]
+    Linear,
+    /// Type 8: approximately median-unbiased, regardless of distribution
+    MedianUnbiased,
+    /// Type 9: approximately unbiased for normally distributed data
+    NormalUnbiased,
+}
+
+/// Pivot selection strategies of the comparison based selection.
+/// All of them share the same partitioning and the introselect guard.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SelectStrategy {
+    /// Mid of three samples, or of three such mids for longer data
+    #[default]
+    MidOf3,
+    /// Recursive mids of mids of three, of about square root of length samples
+    MidOfMids,
+    /// Floyd-Rivest: selection from a sample of size about `n^(2/3)`
+    FloydRivest,
+    /// A single pseudo random pivot
+    RandomPivot,
+    /// Median of medians of groups of five (Blum, Floyd, Pratt, Rivest and Tarjan)
+    MedianOfMedians,
+    /// Mid of three pseudo random samples, drawn by xorshift from the given seed
+    /// (zero is replaced by a default seed). The same seed always gives the same run,
+    /// while inputs built against the fixed sample positions of `MidOf3` lose their effect.
+    SeededSamples(u64),
+}
+
+/// Counters of the work done by one instrumented selection, e.g. by `median_by_stats`.
+/// For the key based selections (`medianu64_stats`, `medu64_stats`), the comparisons are
+/// the tests of bits or bytes of the items and, for the radix search, the swaps are
+/// the items copied into its scratch buffer.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SelectStats {
+    /// Number of comparator calls
+    pub comparisons: usize,
+    /// Number of element swaps, excluding those made while choosing pivots
+    pub swaps: usize,
+    /// Number of partition passes
+    pub passes: usize,
+    /// Length of the range remaining in play after each pass, zero when the pass completed it
+    pub remaining: Vec<usize>,
+}
+
+/// Counts of the data items less than, equal to and greater than a given item, see `rank_range_of`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RankRange {
+    /// Number of items less than the given item
+    pub less: usize,
+    /// Number of items equal to the given item
+    pub equal: usize,
+    /// Number of items greater than the given item
+    pub greater: usize,
+}
+
+impl RankRange {
+    /// The ranks (0-based) that the given item occupies in the sorted data.
+    /// Empty when it does not occur in the data.
+    pub fn ranks(&self) -> core::ops::Range<usize> {
+        self.less..self.less + self.equal
+    }
+}
+
+/// Diagnostic of a claimed median or order statistic, see `is_median_by` and `is_kth_by`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankCheck {
+    /// Counts of the items less than, equal to and greater than the claimed item
+    pub counts: RankRange,
+    /// The ranks, any one of which the claimed item should occupy
+    pub claimed: core::ops::Range<usize>,
+}
+
+impl RankCheck {
+    /// True when the claimed item occupies one of the `claimed` ranks
+    pub fn is_valid(&self) -> bool {
+        let ranks = self.counts.ranks();
+        ranks.start < self.claimed.end && self.claimed.start < ranks.end
+    }
+    /// By how many ranks the claimed item misses the `claimed` ranks: zero when valid,
+    /// negative when it is too small, positive when it is too large
+    pub fn offset(&self) -> isize {
+        let ranks = self.counts.ranks();
+        if self.is_valid() {
+            0
+        } else if ranks.end <= self.claimed.start {
+            ranks.end as isize - 1 - self.claimed.start as isize
+        } else {
+            ranks.start as isize + 1 - self.claimed.end as isize
+        }
+    }
+}
+
+/// Median(s) of a fixed size array of up to `NETWORK_MAX` = 25 items, by comparisons `c`,
+/// using the comparison/exchange network for its size `N`, without any branching on the data
+/// when the comparator allows it. Sizes outside `1..=25` are rejected at compile time.
+/// # Example
+/// ```
+/// use medians::{median_of_array,ConstMedians};
+/// let m = median_of_array([5_u8,9,3,7,1,8,2,4,6],&mut <u8>::cmp);
+/// assert_eq!(m,ConstMedians::Odd(5));
+/// ```
+pub fn median_of_array<T: Copy, const N: usize>(
+    mut a: [T; N],
+    c: &mut impl FnMut(&T, &T) -> Ordering,
+) -> ConstMedians<T> {
+    const {
+        assert!(
+            N > 0 && N <= NETWORK_MAX,
+            "median_of_array: N must be within 1..=25"
+        )
+    };
+    let (m1, m2) = median_network(&mut a, c);
+    if (N & 1) == 1 {
+        ConstMedians::Odd(m1)
+    } else {
+        ConstMedians::Even((m1, m2))
+    }
+}
+
+/// Medians of u8 end type by fast radix search, or by the median networks
+/// for up to `NETWORK_MAX` items
 pub fn medianu8(s: &[u8]) -> Result<(u8, u8), Me> {
     let n = s.len();
     if n == 0 {
         merror("size", "median: zero length data")?;
     };
+    if n <= NETWORK_MAX {
+        let mut a = [0_u8; NETWORK_MAX];
+        a[..n].copy_from_slice(s);
+        return Ok(median_network(&mut a[..n], &mut <u8>::cmp));
+    };
     if (n & 1) == 1 {
-        match n {
-            1 => Ok((s[0], s[0])),
-            3 => {
-                let indx = midof3(s, 0, 1, 2, &mut |a, b| a.cmp(b));
-                Ok((s[indx], s[indx]))
-            }
-            _ => {
-                let m = oddmedianu8(s);
-                Ok((m, m))
-            }
-        }
-    } else if n == 2 {
-        Ok((s[0], s[1]))
+        let m = oddmedianu8(s);
+        Ok((m, m))
     } else {
         let (m1, m2) = evenmedianu8(s);
         Ok((m1, m2))
@@ -76,12 +235,11 @@ pub fn medianu8(s: &[u8]) -> Result<(u8, u8), Me> {
 
 /// Fast medians of u64 end type by binary partitioning.  
 /// Changes the order of the input data
-pub fn medianu64(s: &mut [u64]) -> Result<Medians<u64>, Me> {
+pub fn medianu64(s: &mut [u64]) -> Result<Medians<'_, u64>, Me> {
     let n = s.len();
     match n {
         0 => return merror("size", "medu: zero length data"),
         1 => return Ok(Medians::Odd(&s[0])),
-        2 => return Ok(Medians::Even((&s[0], &s[1]))),
         _ => (),
     };
     if (n & 1) == 1 {
@@ -91,16 +249,120 @@ pub fn medianu64(s: &mut [u64]) -> Result<Medians<u64>, Me> {
     }
 }
 
+/// `medianu64`, also returning the counts of the work done.
+/// Its comparisons are the bit tests of the binary partitions
+pub fn medianu64_stats(s: &mut [u64]) -> Result<(Medians<'_, u64>, SelectStats), Me> {
+    let n = s.len();
+    let mut st = SelectStats::default();
+    match n {
+        0 => return merror("size", "medianu64_stats: zero length data"),
+        1 => return Ok((Medians::Odd(&s[0]), st)),
+        _ => (),
+    };
+    let res = if (n & 1) == 1 {
+        Medians::Odd(oddmedian_bits_recorded(s, &mut Some(&mut st)))
+    } else {
+        Medians::Even(evenmedian_bits_recorded(s, &mut Some(&mut st)))
+    };
+    Ok((res, st))
+}
+
+/// Items of ranks `ks` (0-based, in any order) of u64 data, all found in a single
+/// descent of binary partitions. Changes the order of the input data
+pub fn select_manyu64(s: &mut [u64], ks: &[usize]) -> Result<Vec<u64>, Me> {
+    let sorted = sorted_ranks(ks, s.len())?;
+    let mut res = Vec::with_capacity(sorted.len());
+    let diff = diffbits(s);
+    multiselect_bits(s, 0..s.len(), &sorted, diff, &mut res);
+    Ok(ks
+        .iter()
+        .map(|&k| res[sorted.partition_point(|&j| j < k)])
+        .collect())
+}
+
+/// Indices of the median(s) of u64 data, by binary partitioning.
+/// Ties are broken by the indices: of equal items, the one with the lowest index ranks first.
+/// Does not change the order of the input data.
+pub fn argmedianu64(s: &[u64]) -> Result<ConstMedians<usize>, Me> {
+    if s.is_empty() {
+        return merror("size", "argmedianu64: zero length data");
+    };
+    Ok(argmedian_keys(s.iter().copied()))
+}
+
 /// Medians of u64 end type by fast recursive radix search
+#[deprecated(
+    since = "3.1.0",
+    note = "use `medu64_bytes`, which does not need mutable data"
+)]
 pub fn medu64(s: &mut [u64]) -> Result<(u64, u64), Me> {
-    if (s.len() & 1) == 1 {
-        let byteslice = s.iter().map(|x| x.to_be_bytes()).collect::<Vec<[u8; 8]>>();
-        let res = oddmedu64(&byteslice, 0, s.len() / 2);
-        Ok((res, res))
+    match medu64_bytes(s)? {
+        ConstMedians::Odd(m) => Ok((m, m)),
+        ConstMedians::Even(pair) => Ok(pair),
+    }
+}
+
+/// Medians of u64 end type by fast byte radix search.  
+/// Does not change the order of the input data and allocates at most one scratch buffer
+pub fn medu64_bytes(s: &[u64]) -> Result<ConstMedians<u64>, Me> {
+    let n = s.len();
+    if n == 0 {
+        return merror("size", "medu64_bytes: zero length data");
+    };
+    if (n & 1) == 1 {
+        let (m, _) = radixselu64(s, n / 2, false);
+        Ok(ConstMedians::Odd(m))
     } else {
-        //let (r1,r2) = evenmedu64(&byteslice,0,s.len()/2-1); Ok((r1,r2)) 
-        let (&m1, &m2) = evenmedianu64(s);
-        Ok((m1, m2))
+        Ok(ConstMedians::Even(radixselu64(s, n / 2 - 1, true)))
+    }
+}
+
+/// `medu64_bytes`, also returning the counts of the work done.
+/// Its passes are the byte levels of the radix search
+pub fn medu64_stats(s: &[u64]) -> Result<(ConstMedians<u64>, SelectStats), Me> {
+    let n = s.len();
+    if n == 0 {
+        return merror("size", "medu64_stats: zero length data");
+    };
+    let mut st = SelectStats::default();
+    let res = if (n & 1) == 1 {
+        ConstMedians::Odd(radixselu64_recorded(s, n / 2, false, &mut Some(&mut st)).0)
+    } else {
+        ConstMedians::Even(radixselu64_recorded(s, n / 2 - 1, true, &mut Some(&mut st)))
+    };
+    Ok((res, st))
+}
+
+/// Types that map to unsigned keys of the same width, preserving their order.
+/// Their medians can then be found by fast binary partitioning of the keys.
+pub trait RadixKey: Copy {
+    /// Unsigned key type
+    type Key: KeyBits;
+    /// Order preserving map to the key
+    fn to_key(self) -> Self::Key;
+    /// Inverse of `to_key`
+    fn from_key(key: Self::Key) -> Self;
+}
+
+/// Medians of any `RadixKey` type (e.g. all the primitive integers),
+/// by binary partitioning of their keys. Returns the medians in the original type.
+/// # Example
+/// ```
+/// use medians::{median_radix,ConstMedians};
+/// let v = [-5_i32,3,-1,7,2,-8];
+/// assert_eq!(median_radix(&v).unwrap(),ConstMedians::Even((-1,2)));
+/// ```
+pub fn median_radix<T: RadixKey>(s: &[T]) -> Result<ConstMedians<T>, Me> {
+    let n = s.len();
+    if n == 0 {
+        return merror("size", "median_radix: zero length data");
+    };
+    let mut keys: Vec<T::Key> = s.iter().map(|&x| x.to_key()).collect();
+    if (n & 1) == 1 {
+        Ok(ConstMedians::Odd(T::from_key(*oddmedian_bits(&mut keys))))
+    } else {
+        let (&k1, &k2) = evenmedian_bits(&mut keys);
+        Ok(ConstMedians::Even((T::from_key(k1), T::from_key(k2))))
     }
 }
 
@@ -110,14 +372,54 @@ pub trait Medianf64 {
     fn medf_checked(self) -> Result<f64, Me>;
     /// Median of f64s, including NaNs
     fn medf_unchecked(self) -> f64;
-    /// Iterative weighted median
+    /// Median of f64s by binary partitioning of their keys, NaNs raise an error
+    fn medf_radix(self) -> Result<f64, Me>;
+    /// Iterative weighted median (needs feature `std`)
+    #[cfg(feature = "std")]
     fn medf_weighted(self, ws: Self, eps: f64) -> Result<f64, Me>;
     /// Zero mean/median data produced by subtracting the centre
     fn medf_zeroed(self, centre: f64) -> Vec<f64>;
-    /// Median correlation = cosine of an angle between two zero median vecs
+    /// Median correlation = cosine of an angle between two zero median vecs (needs feature `std`)
+    #[cfg(feature = "std")]
     fn medf_correlation(self, v: Self) -> Result<f64, Me>;
     /// Median of absolute differences (MAD).
     fn madf(self, centre: f64) -> f64;
+    /// Item of rank `k` (0-based, ascending order) of f64s, NaNs raise an error
+    fn select_kf(self, k: usize) -> Result<f64, Me>;
+    /// Quantile `p` in [0,1] of f64s, defined by `method`
+    fn quantile(self, p: f64, method: QuantileMethod) -> Result<f64, Me>;
+    /// Quantiles `ps`, all found in a single descent
+    fn quantiles(self, ps: &[f64], method: QuantileMethod) -> Result<Vec<f64>, Me>;
+    /// Indices of the median(s) of f64s, NaNs raise an error
+    fn argmedf(self) -> Result<ConstMedians<usize>, Me>;
+}
+
+/// Fast 1D medians of single precision floating point data, mirroring `Medianf64`
+pub trait Medianf32 {
+    /// Median of f32s, NaNs removed
+    fn medf_checked(self) -> Result<f32, Me>;
+    /// Median of f32s, including NaNs
+    fn medf_unchecked(self) -> f32;
+    /// Median of f32s by binary partitioning of their keys, NaNs raise an error
+    fn medf_radix(self) -> Result<f32, Me>;
+    /// Iterative weighted median (needs feature `std`)
+    #[cfg(feature = "std")]
+    fn medf_weighted(self, ws: Self, eps: f32) -> Result<f32, Me>;
+    /// Zero mean/median data produced by subtracting the centre
+    fn medf_zeroed(self, centre: f32) -> Vec<f32>;
+    /// Median correlation = cosine of an angle between two zero median vecs (needs feature `std`)
+    #[cfg(feature = "std")]
+    fn medf_correlation(self, v: Self) -> Result<f32, Me>;
+    /// Median of absolute differences (MAD).
+    fn madf(self, centre: f32) -> f32;
+    /// Item of rank `k` (0-based, ascending order) of f32s, NaNs raise an error
+    fn select_kf(self, k: usize) -> Result<f32, Me>;
+    /// Quantile `p` in [0,1] of f32s, defined by `method`
+    fn quantile(self, p: f64, method: QuantileMethod) -> Result<f32, Me>;
+    /// Quantiles `ps`, all found in a single descent
+    fn quantiles(self, ps: &[f64], method: QuantileMethod) -> Result<Vec<f32>, Me>;
+    /// Indices of the median(s) of f32s, NaNs raise an error
+    fn argmedf(self) -> Result<ConstMedians<usize>, Me>;
 }
 
 /// Fast 1D generic medians, plus related methods
@@ -133,9 +435,92 @@ pub trait Median<'a, T> {
     fn uqmedian(self, q: impl Fn(&T) -> u64) -> Result<f64, Me>;
     /// Median by comparison `c`, returns odd/even result
     fn median_by(self, c: &mut impl FnMut(&T, &T) -> Ordering) -> Result<Medians<'a, T>, Me>;
+    /// Median by the order of keys extracted by `f`
+    fn median_by_key<K: Ord>(self, f: impl FnMut(&T) -> K) -> Result<Medians<'a, T>, Me>;
+    /// Median by the order of keys extracted by `f`, computing each key only once
+    fn median_by_cached_key<K: Ord>(self, f: impl FnMut(&T) -> K) -> Result<Medians<'a, T>, Me>;
+    /// Median of `Ord` data by their natural order
+    fn median(self) -> Result<Medians<'a, T>, Me>
+    where
+        T: Ord;
+    /// Median by comparison `c`, also returning the counts of the work done
+    fn median_by_stats(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<(Medians<'a, T>, SelectStats), Me>;
+    /// Median by comparison `c`, with pivots chosen by `strategy`
+    fn median_by_with(
+        self,
+        strategy: SelectStrategy,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Medians<'a, T>, Me>;
+    /// Median by comparison `c` with pivots chosen by `strategy`, quantified to f64 by `q`
+    fn qmedian_by_with(
+        self,
+        strategy: SelectStrategy,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me>;
+    /// Item of rank `k` (0-based) in the order defined by comparison `c`
+    fn select_k_by(self, k: usize, c: &mut impl FnMut(&T, &T) -> Ordering) -> Result<&'a T, Me>;
+    /// Item of rank `k` by comparison `c`, quantified to f64 by `q`
+    fn qselect_k_by(
+        self,
+        k: usize,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me>;
+    /// Index of the item of rank `k` (0-based) by comparison `c`, ties broken by the indices
+    fn argselect_k_by(self, k: usize, c: &mut impl FnMut(&T, &T) -> Ordering) -> Result<usize, Me>;
+    /// Index/indices of the median(s) by comparison `c`, ties broken by the indices
+    fn argmedian_by(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<ConstMedians<usize>, Me>;
+    /// The `k` smallest items by comparison `c`, in ascending order
+    fn smallest_k_by(
+        self,
+        k: usize,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Vec<&'a T>, Me>;
+    /// The `k` largest items by comparison `c`, in descending order
+    fn largest_k_by(
+        self,
+        k: usize,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Vec<&'a T>, Me>;
+    /// Items of the ranks within `rng` by comparison `c`, in ascending order
+    fn partial_sort_by(
+        self,
+        rng: core::ops::Range<usize>,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Vec<&'a T>, Me>;
+    /// Items of all ranks `ks` by comparison `c`, found in a single descent
+    fn select_many_by(
+        self,
+        ks: &[usize],
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Vec<&'a T>, Me>;
+    /// Quantile `p` by comparison `c`, quantified to f64 by `q`
+    fn qquantile_by(
+        self,
+        p: f64,
+        method: QuantileMethod,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me>;
+    /// Quantiles `ps` by comparison `c`, quantified to f64 by `q`
+    fn qquantiles_by(
+        self,
+        ps: &[f64],
+        method: QuantileMethod,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<Vec<f64>, Me>;
     /// Zero mean/median data, produced by subtracting the centre
     fn zeroed(self, centre: f64, quantify: impl Fn(&T) -> f64) -> Result<Vec<f64>, Me>;
-    /// Median correlation = cosine of an angle between two zero median Vecs
+    /// Median correlation = cosine of an angle between two zero median Vecs (needs feature `std`)
+    #[cfg(feature = "std")]
     fn med_correlation(
         self,
         v: Self,
@@ -145,3 +530,68 @@ pub trait Median<'a, T> {
     /// Median of absolute differences (MAD).
     fn mad(self, centre: f64, quantify: impl Fn(&T) -> f64) -> f64;
 }
+
+/// Mutable generic data split in place around a selected item, see `partition_at_k_by`.
+/// The selected item is `equal[0]`.
+pub struct Partitioned<'a, T> {
+    /// Items less than the selected item
+    pub less: &'a mut [T],
+    /// The selected item, followed by all the items equal to it
+    pub equal: &'a mut [T],
+    /// Items greater than the selected item
+    pub greater: &'a mut [T],
+}
+
+impl<T> Partitioned<'_, T> {
+    /// The selected item, e.g. the median
+    pub fn item(&self) -> &T {
+        &self.equal[0]
+    }
+}
+
+/// Medians of mutable generic data, found in place by reordering the data.
+/// Avoids allocating a vector of references, as `medianu64` does for u64s.
+/// Use `Median` instead when the data order must be kept.
+pub trait MedianMut<'a, T> {
+    /// Median by comparison `c`, reorders the data, returns odd/even result
+    fn median_in_place_by(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Medians<'a, T>, Me>;
+    /// Median by comparison `c`, reorders the data, quantified to a single f64 by `q`
+    fn qmedian_in_place_by(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me>;
+    /// Splits the data in place into the items less than, equal to and greater than the item of rank `k`
+    fn partition_at_k_by(
+        self,
+        k: usize,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Partitioned<'a, T>, Me>;
+    /// Splits the data in place into the items less than, equal to and greater than the (lower) median
+    fn partition_at_median_by(
+        self,
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Partitioned<'a, T>, Me>;
+}
+
+/// Medians of data supplied by any iterator, e.g. over a file, a map's values or a filter chain.
+/// The items are collected into one internal buffer, within which the selection proceeds in place.
+/// The results are owned. An empty iterator gives `MedError::Size`.
+pub trait MedianIter: IntoIterator + Sized {
+    /// Median(s) by comparison `c`, returns odd/even owned result
+    fn iter_median_by(
+        self,
+        c: &mut impl FnMut(&Self::Item, &Self::Item) -> Ordering,
+    ) -> Result<ConstMedians<Self::Item>, Me>;
+    /// Median of f64s, NaNs raise an error
+    fn iter_medf(self) -> Result<f64, Me>
+    where
+        Self: IntoIterator<Item = f64>;
+    /// Median(s) of u64s by binary partitioning
+    fn iter_medianu64(self) -> Result<ConstMedians<u64>, Me>
+    where
+        Self: IntoIterator<Item = u64>;
+}
@@ -0,0 +1,389 @@
+use core::cmp::{Ordering, Ordering::*};
+use std::ops::Range;
+use std::thread;
+
+use crate::algos::{
+    diffbits, multiselect_bits, multiselect_mut, part_bits, pivot_estimate, select_mut, KeyBits,
+};
+use crate::{merror, ConstMedians, Me, Median, Medianf64, Medians, RadixKey, SelectStrategy};
+
+/// Data lengths below which the parallel functions just call their sequential counterparts
+pub const PAR_THRESHOLD: usize = 1 << 20;
+
+/// Number of remaining items at which the parallel narrowing hands over to the sequential selection
+const HANDOVER: usize = 1 << 14;
+
+/// Number of threads (and chunks of the data) to use
+fn threads() -> usize {
+    thread::available_parallelism().map_or(1, |t| t.get())
+}
+
+/// Chunks of `s`, one per thread, each with its own range of items still in play
+fn chunks<X>(s: &mut [X]) -> Vec<(&mut [X], Range<usize>)> {
+    let size = s.len().div_ceil(threads()).max(1);
+    s.chunks_mut(size)
+        .map(|ch| {
+            let len = ch.len();
+            (ch, 0..len)
+        })
+        .collect()
+}
+
+/// Minimum of all the `parts` by comparator `c`, or their maximum,
+/// when invoked with swapped arguments of `c`. Each part is scanned in its own thread.
+fn par_extremum<X: Copy + Send + Sync>(
+    parts: Vec<&[X]>,
+    c: &(impl Fn(&X, &X) -> Ordering + Sync),
+) -> X {
+    let mins: Vec<X> = thread::scope(|sc| {
+        let handles: Vec<_> = parts
+            .into_iter()
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                sc.spawn(move || {
+                    *p.iter()
+                        .reduce(|m, x| if c(x, m) == Less { x } else { m })
+                        .expect("par_extremum: empty part")
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("par_extremum: thread panicked"))
+            .collect()
+    });
+    mins.into_iter()
+        .reduce(|m, x| if c(&x, &m) == Less { x } else { m })
+        .expect("par_extremum: no items")
+}
+
+/// Three way partitioning of `rng` in place, by swaps, around an external `pivot` value.
+/// Returns the subscripts `(eqsub, gtsub)`, as `part_mut`.
+fn part_around<X>(
+    s: &mut [X],
+    rng: &Range<usize>,
+    pivot: &X,
+    c: &impl Fn(&X, &X) -> Ordering,
+) -> (usize, usize) {
+    let mut eqsub = rng.start;
+    let mut gtsub = rng.end;
+    let mut i = rng.start;
+    while i < gtsub {
+        match c(&s[i], pivot) {
+            Less => {
+                s.swap(eqsub, i);
+                eqsub += 1;
+                i += 1;
+            }
+            Greater => {
+                gtsub -= 1;
+                s.swap(i, gtsub);
+            }
+            Equal => i += 1,
+        }
+    }
+    (eqsub, gtsub)
+}
+
+/// Lower and upper medians of `s` by comparisons `c`, the same for odd lengths.
+/// All the chunks are partitioned in parallel around a common pivot, the mid of their
+/// own pivot estimates. The counts of the partitions narrow the ranges still in play,
+/// until few enough items remain to be selected sequentially. The sequential selection
+/// also takes over after two unproductive partitions, retaining its worst-case linear time.
+fn par_medians_by<X: Copy + Send + Sync>(
+    s: &mut [X],
+    c: &(impl Fn(&X, &X) -> Ordering + Sync),
+) -> (X, X) {
+    let n = s.len();
+    let need = (n - 1) / 2; // lower median rank
+    let hi = n / 2; // upper median rank, the same as `need` for odd n
+    let mut chunks = chunks(s);
+    let mut base = 0; // rank of the first item in play
+    let mut active = n;
+    let mut unproductive = 0;
+    while active > HANDOVER && unproductive < 2 {
+        let mut estimates: Vec<X> = chunks
+            .iter()
+            .filter(|(_, rng)| !rng.is_empty())
+            .map(|(ch, rng)| ch[pivot_estimate(ch, rng, &mut |a, b| c(a, b))])
+            .collect();
+        estimates.sort_unstable_by(|a, b| c(a, b));
+        let pivot = estimates[estimates.len() / 2];
+        let parts: Vec<(usize, usize)> = thread::scope(|sc| {
+            let handles: Vec<_> = chunks
+                .iter_mut()
+                .map(|(ch, rng)| {
+                    let rng = rng.clone();
+                    sc.spawn(move || part_around(ch, &rng, &pivot, c))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("par_medians_by: thread panicked"))
+                .collect()
+        });
+        let lt: usize = chunks
+            .iter()
+            .zip(&parts)
+            .map(|((_, rng), &(e, _))| e - rng.start)
+            .sum();
+        let eq: usize = parts.iter().map(|&(e, g)| g - e).sum();
+        let (eqsub, gtsub) = (base + lt, base + lt + eq);
+        let lastactive = active;
+        if hi < eqsub {
+            // both medians in the lt partitions
+            for ((_, rng), &(e, _)) in chunks.iter_mut().zip(&parts) {
+                rng.end = e;
+            }
+            active = lt;
+        } else if need >= gtsub {
+            // both medians in the gt partitions
+            for ((_, rng), &(_, g)) in chunks.iter_mut().zip(&parts) {
+                rng.start = g;
+            }
+            active -= lt + eq;
+            base = gtsub;
+        } else if need >= eqsub && hi < gtsub {
+            return (pivot, pivot);
+        } else if need < eqsub {
+            // last place in the lt partitions, the lower median is their maximum
+            let ltparts = chunks
+                .iter()
+                .zip(&parts)
+                .map(|((ch, rng), &(e, _))| &ch[rng.start..e])
+                .collect();
+            return (par_extremum(ltparts, &|a, b| c(b, a)), pivot);
+        } else {
+            // first place in the gt partitions, the upper median is their minimum
+            let gtparts = chunks
+                .iter()
+                .zip(&parts)
+                .map(|((ch, rng), &(_, g))| &ch[g..rng.end])
+                .collect();
+            return (pivot, par_extremum(gtparts, c));
+        };
+        unproductive = if active > lastactive - lastactive / 4 {
+            unproductive + 1
+        } else {
+            0
+        };
+    }
+    // gather the remaining items and finish sequentially
+    let mut rest: Vec<X> = chunks
+        .iter()
+        .flat_map(|(ch, rng)| ch[rng.clone()].iter().copied())
+        .collect();
+    let k = need - base;
+    if hi == need {
+        select_mut(&mut rest, k, SelectStrategy::MidOf3, &mut |a, b| c(a, b));
+        (rest[k], rest[k])
+    } else {
+        multiselect_mut(
+            &mut rest,
+            &[k, k + 1],
+            SelectStrategy::MidOf3,
+            &mut |a, b| c(a, b),
+        );
+        (rest[k], rest[k + 1])
+    }
+}
+
+/// Lower and upper medians of unsigned keys `s`, the same for odd lengths.
+/// All the chunks are partitioned in parallel by the highest bit that differs
+/// within the items in play, whose counts then narrow their ranges, as in `par_medians_by`.
+fn par_medians_bits<K: KeyBits + Send + Sync>(s: &mut [K]) -> (K, K) {
+    let n = s.len();
+    let need = (n - 1) / 2; // lower median rank
+    let hi = n / 2; // upper median rank, the same as `need` for odd n
+    let mut chunks = chunks(s);
+    let mut diff = thread::scope(|sc| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .map(|(ch, _)| sc.spawn(|| diffbits(ch)))
+            .collect();
+        let diffs: Vec<K> = handles
+            .into_iter()
+            .map(|h| h.join().expect("par_medians_bits: thread panicked"))
+            .collect();
+        union_diff(&diffs, chunks.iter().map(|(ch, _)| ch[0]))
+    });
+    let mut base = 0; // rank of the first item in play
+    let mut active = n;
+    while active > HANDOVER {
+        if diff == K::ZERO {
+            // all the items in play are the same
+            let (ch, rng) = chunks
+                .iter()
+                .find(|(_, rng)| !rng.is_empty())
+                .expect("par_medians_bits: no items");
+            return (ch[rng.start], ch[rng.start]);
+        };
+        let bitval = diff.highest_bit();
+        let parts: Vec<(usize, K, K)> = thread::scope(|sc| {
+            let handles: Vec<_> = chunks
+                .iter_mut()
+                .map(|(ch, rng)| {
+                    let rng = rng.clone();
+                    sc.spawn(move || part_bits(ch, &rng, bitval))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("par_medians_bits: thread panicked"))
+                .collect()
+        });
+        let lt: usize = chunks
+            .iter()
+            .zip(&parts)
+            .map(|((_, rng), &(g, _, _))| g - rng.start)
+            .sum();
+        let gtsub = base + lt;
+        if hi < gtsub {
+            // both medians in the lt partitions
+            let mut diffs = Vec::with_capacity(parts.len());
+            for ((_, rng), &(g, ltdiff, _)) in chunks.iter_mut().zip(&parts) {
+                if g > rng.start {
+                    diffs.push(ltdiff);
+                };
+                rng.end = g;
+            }
+            diff = union_diff(&diffs, firsts(&chunks));
+            active = lt;
+        } else if need >= gtsub {
+            // both medians in the gt partitions
+            let mut diffs = Vec::with_capacity(parts.len());
+            for ((_, rng), &(g, _, gtdiff)) in chunks.iter_mut().zip(&parts) {
+                if g < rng.end {
+                    diffs.push(gtdiff);
+                };
+                rng.start = g;
+            }
+            diff = union_diff(&diffs, firsts(&chunks));
+            active -= lt;
+            base = gtsub;
+        } else {
+            // split between the partitions: maximum of lt and minimum of gt
+            let ltparts = chunks
+                .iter()
+                .zip(&parts)
+                .map(|((ch, rng), &(g, _, _))| &ch[rng.start..g])
+                .collect();
+            let gtparts = chunks
+                .iter()
+                .zip(&parts)
+                .map(|((ch, rng), &(g, _, _))| &ch[g..rng.end])
+                .collect();
+            return (
+                par_extremum(ltparts, &|a, b| b.cmp(a)),
+                par_extremum(gtparts, &K::cmp),
+            );
+        };
+    }
+    // gather the remaining items and finish sequentially
+    let mut rest: Vec<K> = chunks
+        .iter()
+        .flat_map(|(ch, rng)| ch[rng.clone()].iter().copied())
+        .collect();
+    let k = need - base;
+    let mut res = Vec::with_capacity(2);
+    let restdiff = diffbits(&rest);
+    let len = rest.len();
+    multiselect_bits(&mut rest, 0..len, &[k, hi - base], restdiff, &mut res);
+    (res[0], res[1])
+}
+
+/// First items in play of all the non empty chunks
+fn firsts<'a, K: Copy>(chunks: &'a [(&mut [K], Range<usize>)]) -> impl Iterator<Item = K> + 'a {
+    chunks
+        .iter()
+        .filter(|(_, rng)| !rng.is_empty())
+        .map(|(ch, rng)| ch[rng.start])
+}
+
+/// Bits that differ within the union of several non empty parts, given the bits that differ
+/// within each part and any one item of each part. The bits that do not differ within any part
+/// are those of its items, so they differ within the union when they differ between these items.
+fn union_diff<K: KeyBits>(diffs: &[K], mut items: impl Iterator<Item = K>) -> K {
+    let mut diff = diffs.iter().fold(K::ZERO, |d, &x| d | x);
+    if let Some(first) = items.next() {
+        for x in items {
+            diff = diff | (x ^ first);
+        }
+    };
+    diff
+}
+
+/// Medians of u64 data, by binary partitioning of parallel chunks, see `medianu64`.
+/// Changes the order of the input data. Below `PAR_THRESHOLD` items, `medianu64` is used.
+pub fn par_medianu64(s: &mut [u64]) -> Result<ConstMedians<u64>, Me> {
+    let n = s.len();
+    if n < PAR_THRESHOLD {
+        return Ok(match crate::medianu64(s)? {
+            Medians::Odd(&m) => ConstMedians::Odd(m),
+            Medians::Even((&m1, &m2)) => ConstMedians::Even((m1, m2)),
+        });
+    };
+    let (m1, m2) = par_medians_bits(s);
+    Ok(if (n & 1) == 1 {
+        ConstMedians::Odd(m1)
+    } else {
+        ConstMedians::Even((m1, m2))
+    })
+}
+
+/// Median of f64 data, by binary partitioning of their `RadixKey` keys in parallel chunks.
+/// NaNs raise an error. Below `PAR_THRESHOLD` items, `medf_checked` is used.
+pub fn par_medf(s: &[f64]) -> Result<f64, Me> {
+    let n = s.len();
+    if n < PAR_THRESHOLD {
+        return s.medf_checked();
+    };
+    let mut keys = vec![0_u64; n];
+    let size = n.div_ceil(threads());
+    let nans = thread::scope(|sc| {
+        let handles: Vec<_> = s
+            .chunks(size)
+            .zip(keys.chunks_mut(size))
+            .map(|(src, dst)| {
+                sc.spawn(move || {
+                    for (k, &x) in dst.iter_mut().zip(src) {
+                        *k = x.to_key();
+                    }
+                    src.iter().any(|x| x.is_nan())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .any(|h| h.join().expect("par_medf: thread panicked"))
+    });
+    if nans {
+        return merror("nan", "par_medf: Nan in input!");
+    };
+    let (k1, k2) = par_medians_bits(&mut keys);
+    if (n & 1) == 1 {
+        Ok(f64::from_key(k1))
+    } else {
+        // halved first, so that huge medians do not overflow to infinity
+        Ok(f64::from_key(k1) / 2.0 + f64::from_key(k2) / 2.0)
+    }
+}
+
+/// Median(s) of generic data by comparison `c`, which must be `Sync`,
+/// partitioned in parallel chunks of references. Below `PAR_THRESHOLD` items, `median_by` is used.
+pub fn par_median_by<T: Sync>(
+    s: &[T],
+    c: impl Fn(&T, &T) -> Ordering + Sync,
+) -> Result<Medians<'_, T>, Me> {
+    let n = s.len();
+    if n < PAR_THRESHOLD {
+        return s.median_by(&mut |a, b| c(a, b));
+    };
+    let mut refs: Vec<&T> = s.iter().collect();
+    let (m1, m2) = par_medians_by(&mut refs, &|a: &&T, b: &&T| c(a, b));
+    Ok(if (n & 1) == 1 {
+        Medians::Odd(m1)
+    } else {
+        Medians::Even((m1, m2))
+    })
+}
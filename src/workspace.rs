@@ -0,0 +1,143 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::algos::{medians_block, multiselect_mut, select_mut};
+use crate::{medianu64, merror, Me, Medians, SelectStrategy};
+
+/// Scratch buffers for repeated medians, e.g. of same sized windows in a realtime loop.
+/// The buffers are cleared and refilled by each call, so once they have grown
+/// to the largest data size, the calls do not allocate at all.
+/// The methods mirror those of `Medianf64` and `Median`, taking the data as an argument.
+#[derive(Debug, Default, Clone)]
+pub struct MedianWorkspace {
+    /// copies of f64 data, or of their deviations
+    floats: Vec<f64>,
+    /// u64 keys quantified from generic data
+    keys: Vec<u64>,
+    /// indices of generic data, selected instead of references
+    indices: Vec<usize>,
+}
+
+impl MedianWorkspace {
+    /// New workspace with empty buffers, they grow on first use
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// New workspace with buffers for data of up to `n` items, so that not even the first calls allocate.
+    pub fn with_capacity(n: usize) -> Self {
+        MedianWorkspace {
+            floats: Vec::with_capacity(n),
+            keys: Vec::with_capacity(n),
+            indices: Vec::with_capacity(n),
+        }
+    }
+
+    /// Median of f64 data, as `Medianf64::medf_unchecked`.
+    /// Returns zero for empty data.
+    pub fn medf_unchecked(&mut self, s: &[f64]) -> f64 {
+        self.floats.clear();
+        self.floats.extend_from_slice(s);
+        medf_buffer(&mut self.floats)
+    }
+
+    /// Median of f64 data, as `Medianf64::medf_checked`.
+    /// Returns `size` error for empty data and `nan` error when any item is a NaN.
+    pub fn medf_checked(&mut self, s: &[f64]) -> Result<f64, Me> {
+        if s.is_empty() {
+            return merror("size", "medf_checked: zero length data");
+        };
+        if s.iter().any(|x| x.is_nan()) {
+            return merror("nan", "medf_checked: Nan in input!");
+        };
+        Ok(self.medf_unchecked(s))
+    }
+
+    /// Median of absolute differences (MAD) of f64 data from `centre`, as `Medianf64::madf`
+    pub fn madf(&mut self, s: &[f64], centre: f64) -> f64 {
+        self.floats.clear();
+        self.floats.extend(s.iter().map(|&x| (x - centre).abs()));
+        medf_buffer(&mut self.floats)
+    }
+
+    /// Median of generic data quantified to u64 by `q`, as `Median::uqmedian`
+    pub fn uqmedian<T>(&mut self, s: &[T], q: impl Fn(&T) -> u64) -> Result<f64, Me> {
+        if s.is_empty() {
+            return merror("size", "uqmedian: zero length data");
+        };
+        self.keys.clear();
+        self.keys.extend(s.iter().map(q));
+        match medianu64(&mut self.keys)? {
+            Medians::Odd(&r) => Ok(r as f64),
+            Medians::Even((&r1, &r2)) => Ok((r1 as f64 + r2 as f64) / 2_f64),
+        }
+    }
+
+    /// Median(s) of generic data by comparison `c`, as `Median::median_by`.
+    /// The selection proceeds over a buffer of indices, so the results refer into `s`.
+    pub fn median_by<'a, T>(
+        &mut self,
+        s: &'a [T],
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+    ) -> Result<Medians<'a, T>, Me> {
+        let n = s.len();
+        if n == 0 {
+            return merror("size", "median_by: zero length data");
+        };
+        self.indices.clear();
+        self.indices.extend(0..n);
+        let need = (n - 1) / 2; // lower median position
+        let mut ci = |&i: &usize, &j: &usize| c(&s[i], &s[j]);
+        if (n & 1) == 1 {
+            select_mut(&mut self.indices, need, SelectStrategy::MidOf3, &mut ci);
+            Ok(Medians::Odd(&s[self.indices[need]]))
+        } else {
+            multiselect_mut(
+                &mut self.indices,
+                &[need, need + 1],
+                SelectStrategy::MidOf3,
+                &mut ci,
+            );
+            Ok(Medians::Even((
+                &s[self.indices[need]],
+                &s[self.indices[need + 1]],
+            )))
+        }
+    }
+
+    /// Median of generic data by comparison `c`, quantified to a single f64 by `q`,
+    /// as `Median::qmedian_by`
+    pub fn qmedian_by<T>(
+        &mut self,
+        s: &[T],
+        c: &mut impl FnMut(&T, &T) -> Ordering,
+        q: impl Fn(&T) -> f64,
+    ) -> Result<f64, Me> {
+        match self.median_by(s, c)? {
+            Medians::Odd(m) => Ok(q(m)),
+            Medians::Even((m1, m2)) => Ok((q(m1) + q(m2)) / 2.0),
+        }
+    }
+
+    /// Median of absolute differences (MAD) of generic data quantified by `q`, from `centre`,
+    /// as `Median::mad`
+    pub fn mad<T>(&mut self, s: &[T], centre: f64, q: impl Fn(&T) -> f64) -> f64 {
+        self.floats.clear();
+        self.floats.extend(s.iter().map(|x| (q(x) - centre).abs()));
+        medf_buffer(&mut self.floats)
+    }
+}
+
+/// Median of the f64s in buffer `s`, selected in place. Zero for an empty buffer.
+fn medf_buffer(s: &mut [f64]) -> f64 {
+    let n = s.len();
+    if n == 0 {
+        return 0_f64;
+    };
+    let (med1, med2) = medians_block(s, (n - 1) / 2, n / 2, &mut <f64>::total_cmp);
+    if (n & 1) == 1 {
+        med1
+    } else {
+        (med1 + med2) / 2.0
+    }
+}
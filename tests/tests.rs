@@ -1,74 +1,102 @@
+//! Tests and usage examples of crate medians
 #![allow(unused_imports)]
 #![allow(dead_code)]
+use core::cmp::{Ordering, Ordering::*};
 #[cfg(test)]
 use indxvec::{here, printing::*, Indices, Mutops, Printing, Vecops};
-use medians::{*,algos::*};
+use medians::{algos::*, lanes::*, parallel::*, workspace::*, *};
 use ran::*;
-use core::cmp::{Ordering, Ordering::*};
 use std::convert::From;
 use std::error::Error;
 use times::{benchf64, benchu64, benchu8, mutbenchf64, mutbenchu64};
 
 #[test]
 fn partbin() -> Result<(), Me> {
-    let mut data = [257_u64,9,8,7,6,5,4,3,2,1];
-    println!("Data: {}",data.gr());  
+    let mut data = [257_u64, 9, 8, 7, 6, 5, 4, 3, 2, 1];
+    println!("Data: {}", data.gr());
     let n = data.len();
-    let gtsub = data.part_binary( &(0..n), 3);
-    println!("Partitioned by bit 3: {},{}",data[..gtsub].gr(),data[gtsub..].gr());
-    println!("Median: {}",evenmedianu64(&mut data).gr());
+    let gtsub = data.part_binary(&(0..n), 3);
+    println!(
+        "Partitioned by bit 3: {},{}",
+        data[..gtsub].gr(),
+        data[gtsub..].gr()
+    );
+    println!("Median: {}", evenmedianu64(&mut data).gr());
     Ok(())
 }
 
 #[test]
 fn ftest() -> Result<(), Me> {
-let a = vec![
-    100.0, 
-    163.6170150950381,
-    224.6127142531872,
-    239.91368100304916,
-    345.1674002412572,
-    402.88833594261706,
-    423.6406741377381,
-    472.6292699764225,
-    487.23306678749594,
-    490.94434592125606,
-    511.16658896980687,
-    516.3472076946555,
-    523.052566308903,
-    563.6784311991111,
-    586.7283185517608,
-    633.5580942760708,
-    678.4956618813414,
-    708.2452516626092,
-    741.9710552209048,
-    763.476192474483,
-    768.6249939324011,
-    777.1952444919513,
-    785.2192860329102,
-    785.3178558989187,
-    858.0319001781837,
-    927.4228569429413,
-    952.453888947949,
-    1067.6089037099757,
-];
-eprintln!("Median: {} ", a.medf_unchecked());
-Ok(())
+    let a: Vec<f64> = vec![
+        100.0,
+        163.6170150950381,
+        224.6127142531872,
+        239.91368100304916,
+        345.1674002412572,
+        402.88833594261706,
+        423.6406741377381,
+        472.6292699764225,
+        487.23306678749594,
+        490.94434592125606,
+        511.16658896980687,
+        516.3472076946555,
+        523.052566308903,
+        563.6784311991111,
+        586.7283185517608,
+        633.5580942760708,
+        678.4956618813414,
+        708.2452516626092,
+        741.9710552209048,
+        763.476192474483,
+        768.6249939324011,
+        777.1952444919513,
+        785.2192860329102,
+        785.3178558989187,
+        858.0319001781837,
+        927.4228569429413,
+        952.453888947949,
+        1067.6089037099757,
+    ];
+    eprintln!("Median: {} ", a.medf_unchecked());
+    Ok(())
 }
 
 #[test]
 fn parting() -> Result<(), Me> {
     let data = [
-        5.,8.,7.,6.,5.,4.,3.,2.,-f64::NAN,
-        1.,0.,1.,-2.,3.,4.,-5.,f64::NAN,f64::NAN,
-        6.,7.,7.,
+        5.,
+        8.,
+        7.,
+        6.,
+        5.,
+        4.,
+        3.,
+        2.,
+        -f64::NAN,
+        1.,
+        0.,
+        1.,
+        -2.,
+        3.,
+        4.,
+        -5.,
+        f64::NAN,
+        f64::NAN,
+        6.,
+        7.,
+        7.,
     ];
-    println!("Data; {}",data.gr());
+    println!("Data; {}", data.gr());
     let len = data.len();
-    let mut refdata = data.ref_vec(0..data.len()); 
+    let mut refdata = data.ref_vec(0..data.len());
     let (eqsub, gtsub) = <&mut [f64]>::part(&mut refdata, &(0..len), &mut <f64>::total_cmp);
-    println!("Pivot {}. {} items found equal to the pivot", data[0].yl(), (gtsub - eqsub).yl()); 
-    println!("Partitions:\n{}, {}, {}\n",        
+    println!(
+        "Pivot {}. {} items found equal to the pivot",
+        data[0].yl(),
+        (gtsub - eqsub).yl()
+    );
+    println!(
+        "Partitions:\n{}, {}, {}\n",
         refdata[0..eqsub].gr(), //to_plainstr(),
         refdata[eqsub..gtsub].gr(),
         refdata[gtsub..len].gr()
@@ -90,10 +118,11 @@ fn text() {
         Tee hee heee, piddle piddledy dee, quoth he.";
     let v = song.split(' ').collect::<Vec<_>>();
     println!("{}", v.gr()); // Display
-    // v.mutisort(0..v.len(),|&a,&b| a.len().cmp(&b.len()));
+                            // v.mutisort(0..v.len(),|&a,&b| a.len().cmp(&b.len()));
     println!(
-        "Insert log sorted by word lengths: {}", 
-        v.isort_refs(0..v.len(),|&a,&b| a.len().cmp(&b.len())).gr() 
+        "Insert log sorted by word lengths: {}",
+        v.isort_refs(0..v.len(), |&a, &b| a.len().cmp(&b.len()))
+            .gr()
     );
     println!(
         "Median word(s) by length: {GR}{}{UN}",
@@ -108,6 +137,29 @@ fn text() {
             .median_by(&mut <&str>::cmp)
             .expect("text(): median_by lexicon failed\n")
     );
+    let lens = |m: Medians<&str>| match m {
+        Medians::Odd(w) => (w.len(), w.len()),
+        Medians::Even((w1, w2)) => (w1.len(), w2.len()),
+    };
+    let bylen = lens(
+        v.median_by(&mut |a, b| a.len().cmp(&b.len()))
+            .expect("text(): median_by length failed\n"),
+    );
+    let cached = v
+        .median_by_cached_key(|w| w.len())
+        .expect("text(): cached key failed\n");
+    println!("Median word(s) by cached length: {GR}{cached}{UN}");
+    assert_eq!(lens(cached), bylen);
+    assert_eq!(
+        lens(v.median_by_key(|w| w.len()).expect("text(): key failed\n")),
+        bylen
+    );
+    let lexicon = v.median().expect("text(): median failed\n");
+    let cachedlex = v
+        .median_by_cached_key(|&w| w)
+        .expect("text(): cached lexicon failed\n");
+    assert_eq!(lexicon.to_string(), cachedlex.to_string());
+    assert!(v[..0].median().is_err());
 }
 
 #[test]
@@ -133,23 +185,34 @@ fn medf64() -> Result<(), Me> {
         best_two_refs(&vr, 0..len, &mut |a: &f64, b: &f64| b.total_cmp(a)).gr()
     );
     let (eqsub, gtsub) = <&mut [f64]>::part(&mut vr, &(0..v.len()), &mut <f64>::total_cmp);
-    println!("Partitioning (pivot {}, commas separate the subranges): {}", v[0].yl(),(eqsub,gtsub).gr()); 
-    println!("{GR}[{}, {}, {}]{UN}\nNumber of items equal to the pivot {}",
+    println!(
+        "Partitioning (pivot {}, commas separate the subranges): {}",
+        v[0].yl(),
+        (eqsub, gtsub).gr()
+    );
+    println!(
+        "{GR}[{}, {}, {}]{UN}\nNumber of items equal to the pivot {}",
         vr[0..eqsub].to_plainstr(),
         vr[eqsub..gtsub].to_plainstr(),
         vr[gtsub..len].to_plainstr(),
-        (gtsub - eqsub).yl()     
+        (gtsub - eqsub).yl()
     );
     let median = v.medf_checked()?;
     let mad = v.madf(median);
     println!("Median±mad: {GR}{}±{}{UN}", median, mad);
-    println!("Mean:       {GR}{}{UN}", v.iter().sum::<f64>()/(len as f64));
+    println!(
+        "Mean:       {GR}{}{UN}",
+        v.iter().sum::<f64>() / (len as f64)
+    );
     println!(
         "Weighted median: {GR}{}{UN} ",
         v.medf_weighted(&weights, 0.00001)?
     );
-    let prodsum:f64 = v.iter().zip(weights.iter()).map(|(x,w)| x*w ).sum();
-    println!("Weighted mean:   {GR}{}{UN}", prodsum/weights.iter().sum::<f64>());
+    let prodsum: f64 = v.iter().zip(weights.iter()).map(|(x, w)| x * w).sum();
+    println!(
+        "Weighted mean:   {GR}{}{UN}",
+        prodsum / weights.iter().sum::<f64>()
+    );
     Ok(())
 }
 
@@ -166,27 +229,36 @@ fn errors() -> Result<(), Me> {
     let n = 10_usize; // number of vectors to test for each magnitude
                       // set_seeds(33333);
     for d in [10, 50, 100, 1000, 10000, 100000] {
-        let mut error = 0_i64;
+        let mut error = 0_isize;
         trait Eq: PartialEq<Self> {}
         impl Eq for f64 {}
         for _ in 0..n {
-            let Ok(mut v) = ranv_u64(d) else {
+            let Ok(v) = ranv_u64(d) else {
                 return merror("other", "Random vec genertion failed");
             };
-            let (m1,m2) = medu64(&mut v)?;
-            error += qbalance(&v, &((m1 as f64+m2 as f64)/2.0), |&f| f as f64);
+            let ConstMedians::Even((m1, m2)) = medu64_bytes(&v)? else {
+                return merror("other", "even medians expected");
+            };
+            error += is_kth_by(&v, d / 2 - 1, &m1, &mut <u64>::cmp)?
+                .offset()
+                .abs();
+            error += is_kth_by(&v, d / 2, &m2, &mut <u64>::cmp)?.offset().abs();
         }
         println!("Even length {GR}{d}{UN}, repeats: {GR}{n}{UN}, errors: {GR}{error}{UN}");
-        error = 0_i64;
+        error = 0_isize;
         for _ in 0..n {
-            let Ok(mut v) = ranv_u64(d + 1) else {
+            let Ok(v) = ranv_u64(d + 1) else {
                 return merror("other", "Random vec genertion failed");
             };
             // v
             //    .as_slice()
             //    .medf_unchecked();
-            let (m1,m2) = medu64(&mut v)?;
-            error += qbalance(&v, &((m1 as f64+m2 as f64)/2.0), |&f| f as f64);
+            let ConstMedians::Odd(m) = medu64_bytes(&v)? else {
+                return merror("other", "odd median expected");
+            };
+            let check = is_median_by(&v, &m, &mut <u64>::cmp)?;
+            assert!(check.is_valid(), "{check:?}");
+            error += check.offset().abs();
         }
         println!(
             "Odd  length {GR}{}{UN}, repeats: {GR}{n}{UN}, errors: {GR}{error}{UN}",
@@ -198,55 +270,1034 @@ fn errors() -> Result<(), Me> {
 
 #[test]
 fn comparison() {
-println!("Comparison tests running, please wait....");
-const NAMES: [&str; 5] = ["median_by","medf_unchecked","uqmedian","medianu64","medu64"];
-
-const CLOSURESU64: [fn(&mut [u64]); 5] = [
-    |v: &mut [_]| {
-        v.median_by(&mut <u64>::cmp)
-            .expect("median_by closure failed");
-    },
-
-    |v: &mut [_]| {
-        let vf:Vec<f64> = v.iter().map(|&x| x as f64).collect();
-        vf.medf_unchecked();
-    //    .expect("medf_checked found NaN");
-    },
-
-    |v: &mut [_]| { // already in u64, so using identity quantifier
-        v.uqmedian(|&x| x)
-        .expect("uqmedian error");
-    },
-
-    |v: &mut [_]| {
-        medianu64(v)
-        .expect("uqmedian error");
-    },
-
-    |v: &mut [_]| {
-        medu64(v)
-        .expect("uqmedian error");
-    }
-
-    /*
-    |v: &[_]| {
-        let mut sorted: Vec<&f64> = v.iter().collect();
-        sorted.sort_unstable_by(|&a, &b| a.total_cmp(b));
-        // sorted[sorted.len()/2];
-    },
-
-    |v: &[_]| {
-        v.qmedian_by(&mut <f64>::total_cmp,|&x| x)
-        .expect("even median closure failed");
-    },
-    */
-
-    /*
-    |v: &[_]| {
-        medianu8(v)
-            .expect("medianu8 closure failed");
-    }
-    */
-];
+    println!("Comparison tests running, please wait....");
+    const NAMES: [&str; 5] = [
+        "median_by",
+        "medf_unchecked",
+        "uqmedian",
+        "medianu64",
+        "medu64_bytes",
+    ];
+
+    const CLOSURESU64: [fn(&mut [u64]); 5] = [
+        |v: &mut [_]| {
+            v.median_by(&mut <u64>::cmp)
+                .expect("median_by closure failed");
+        },
+        |v: &mut [_]| {
+            let vf: Vec<f64> = v.iter().map(|&x| x as f64).collect();
+            vf.medf_unchecked();
+            //    .expect("medf_checked found NaN");
+        },
+        |v: &mut [_]| {
+            // already in u64, so using identity quantifier
+            v.uqmedian(|&x| x).expect("uqmedian error");
+        },
+        |v: &mut [_]| {
+            medianu64(v).expect("uqmedian error");
+        },
+        |v: &mut [_]| {
+            medu64_bytes(v).expect("uqmedian error");
+        }, /*
+           |v: &[_]| {
+               let mut sorted: Vec<&f64> = v.iter().collect();
+               sorted.sort_unstable_by(|&a, &b| a.total_cmp(b));
+               // sorted[sorted.len()/2];
+           },
+
+           |v: &[_]| {
+               v.qmedian_by(&mut <f64>::total_cmp,|&x| x)
+               .expect("even median closure failed");
+           },
+           */
+
+           /*
+           |v: &[_]| {
+               medianu8(v)
+                   .expect("medianu8 closure failed");
+           }
+           */
+    ];
     mutbenchu64(100000..100010, 1, 10, &NAMES, &CLOSURESU64);
 }
+
+/// Random u64 data of small, odd and even lengths,
+/// each also bunched into a few values repeated many times
+fn u64_fixtures() -> Vec<Vec<u64>> {
+    let mut res = Vec::new();
+    for d in [1_usize, 2, 3, 4, 5, 10, 101, 1000, 1001] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        let w = v.iter().map(|&x| x % 7).collect();
+        res.push(v);
+        res.push(w);
+    }
+    res
+}
+
+#[test]
+fn select_k() -> Result<(), Me> {
+    for data in u64_fixtures() {
+        let d = data.len();
+        let mut sorted = data.clone();
+        sorted.sort_unstable();
+        let vf: Vec<f64> = data.iter().map(|&x| x as f64).collect();
+        for k in (0..d).step_by(1 + d / 50) {
+            assert_eq!(*data.select_k_by(k, &mut <u64>::cmp)?, sorted[k]);
+            assert_eq!(
+                data.qselect_k_by(k, &mut <u64>::cmp, |&x| x as f64)?,
+                sorted[k] as f64
+            );
+            assert_eq!(vf.select_kf(k)?, sorted[k] as f64);
+        }
+        assert!(data.select_k_by(d, &mut <u64>::cmp).is_err());
+    }
+    let v = [
+        9_f64, 10., 18., 17., 16., 15., 14., 1., 2., 3., 4., 5., 6., 7., 8.,
+    ];
+    println!("Data: {}", v.gr());
+    println!(
+        "Deciles 1 and 9: {} {}",
+        v.select_kf(v.len() / 10)?.gr(),
+        v.select_kf(9 * v.len() / 10)?.gr()
+    );
+    Ok(())
+}
+
+#[test]
+fn multiselect() -> Result<(), Me> {
+    for d in [1_usize, 2, 3, 10, 101, 1000, 10001] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        let w: Vec<u64> = v.iter().map(|&x| x % 5).collect();
+        for data in [v, w] {
+            let mut sorted = data.clone();
+            sorted.sort_unstable();
+            // deciles, in descending order and with a repeat
+            let mut ks: Vec<usize> = (0..=10).rev().map(|i| i * (d - 1) / 10).collect();
+            ks.push(d / 2);
+            let res = data.select_many_by(&ks, &mut <u64>::cmp)?;
+            let mut u = data.clone();
+            let resu = select_manyu64(&mut u, &ks)?;
+            for (i, &k) in ks.iter().enumerate() {
+                assert_eq!(*res[i], sorted[k]);
+                assert_eq!(resu[i], sorted[k]);
+            }
+            assert!(data.select_many_by(&[0, d], &mut <u64>::cmp).is_err());
+        }
+    }
+    let v = ranv_u64(21).expect("Random vec generation failed");
+    println!("Data: {}", v.gr());
+    println!(
+        "Min, quartiles, max: {}",
+        v.select_many_by(&[0, 5, 10, 15, 20], &mut <u64>::cmp)?.gr()
+    );
+    Ok(())
+}
+
+#[test]
+fn quantiles() -> Result<(), Me> {
+    use QuantileMethod::*;
+    let v = [7_f64, 2., 10., 4., 1., 9., 3., 6., 8., 5.];
+    let methods = [
+        InvertedCdf,
+        AveragedInvertedCdf,
+        ClosestObservation,
+        InterpolatedInvertedCdf,
+        Hazen,
+        Weibull,
+        Linear,
+        MedianUnbiased,
+        NormalUnbiased,
+    ];
+    // R: quantile(1:10, c(0.25,0.5), type=1:9)
+    let expected = [
+        [3., 5.],
+        [3., 5.5],
+        [2., 5.],
+        [2.5, 5.],
+        [3., 5.5],
+        [2.75, 5.5],
+        [3.25, 5.5],
+        [2.9166666666666665, 5.5],
+        [2.9375, 5.5],
+    ];
+    for (&method, exp) in methods.iter().zip(expected) {
+        let res = v.quantiles(&[0.25, 0.5], method)?;
+        assert!(
+            (res[0] - exp[0]).abs() < 1e-12,
+            "{method:?}: {} != {}",
+            res[0],
+            exp[0]
+        );
+        assert!(
+            (res[1] - exp[1]).abs() < 1e-12,
+            "{method:?}: {} != {}",
+            res[1],
+            exp[1]
+        );
+        assert_eq!(v.quantile(0., method)?, 1.);
+        assert_eq!(v.quantile(1., method)?, 10.);
+        let u: Vec<u64> = v.iter().map(|&x| x as u64).collect();
+        assert_eq!(
+            u.qquantiles_by(&[0.25, 0.5], method, &mut <u64>::cmp, |&x| x as f64)?,
+            res
+        );
+    }
+    assert_eq!(v.quantile(0.5, Linear)?, v.medf_checked()?);
+    assert!(v.quantile(1.5, Linear).is_err());
+    assert!(v.quantile(f64::NAN, Linear).is_err());
+    println!(
+        "Deciles (Linear): {}",
+        v.quantiles(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9], Linear)?
+            .gr()
+    );
+    Ok(())
+}
+
+/// McIlroy's adversary (1999), which lazily assigns values to the data items
+/// during the comparisons, so as to force quadratic behaviour of quicksort-like algorithms
+struct Adversary {
+    val: Vec<usize>,
+    gas: usize,
+    nsolid: usize,
+    candidate: usize,
+    count: usize,
+}
+
+impl Adversary {
+    fn new(n: usize) -> Self {
+        Adversary {
+            val: vec![n; n],
+            gas: n,
+            nsolid: 0,
+            candidate: 0,
+            count: 0,
+        }
+    }
+    fn cmp(&mut self, x: usize, y: usize) -> Ordering {
+        self.count += 1;
+        if self.val[x] == self.gas && self.val[y] == self.gas {
+            if x == self.candidate {
+                self.val[x] = self.nsolid
+            } else {
+                self.val[y] = self.nsolid
+            };
+            self.nsolid += 1;
+        };
+        if self.val[x] == self.gas {
+            self.candidate = x
+        } else if self.val[y] == self.gas {
+            self.candidate = y
+        };
+        self.val[x].cmp(&self.val[y])
+    }
+}
+
+#[test]
+fn introselect() -> Result<(), Me> {
+    for n in [1000_usize, 10000, 100000, 100001] {
+        let v: Vec<usize> = (0..n).collect();
+        let mut adv = Adversary::new(n);
+        v.median_by(&mut |&x, &y| adv.cmp(x, y))?;
+        println!(
+            "Adversarial median of {GR}{n}{UN} items took {GR}{}{UN} comparisons",
+            adv.count
+        );
+        assert!(adv.count < 20 * n, "quadratic behaviour of median_by");
+        let mut adv = Adversary::new(n);
+        v.select_many_by(&[n / 10, n / 2, 9 * n / 10], &mut |&x, &y| adv.cmp(x, y))?;
+        assert!(adv.count < 40 * n, "quadratic behaviour of select_many_by");
+    }
+    Ok(())
+}
+
+#[test]
+fn strategies() -> Result<(), Me> {
+    use SelectStrategy::*;
+    let strategies = [
+        MidOf3,
+        MidOfMids,
+        FloydRivest,
+        RandomPivot,
+        MedianOfMedians,
+        SeededSamples(42),
+    ];
+    for d in [3_usize, 4, 20, 101, 1000, 5000, 100000, 100001] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        let w: Vec<u64> = v.iter().map(|&x| x % 9).collect();
+        for data in [v, w] {
+            let mut sorted = data.clone();
+            sorted.sort_unstable();
+            for strategy in strategies {
+                match data.median_by_with(strategy, &mut <u64>::cmp)? {
+                    Medians::Odd(&m) => assert_eq!(m, sorted[d / 2], "{strategy:?}"),
+                    Medians::Even((&m1, &m2)) => {
+                        assert_eq!((m1, m2), (sorted[d / 2 - 1], sorted[d / 2]), "{strategy:?}")
+                    }
+                };
+            }
+        }
+    }
+    // numbers of comparisons, on random and on adversarial data
+    let n = 100001;
+    let v = ranv_f64(n).expect("Random vec generation failed");
+    let ids: Vec<usize> = (0..n).collect();
+    for strategy in strategies {
+        let mut count = 0_usize;
+        let m = v.qmedian_by_with(
+            strategy,
+            &mut |a, b| {
+                count += 1;
+                a.total_cmp(b)
+            },
+            |&x| x,
+        )?;
+        assert_eq!(m, v.medf_checked()?);
+        let mut adv = Adversary::new(n);
+        ids.median_by_with(strategy, &mut |&x, &y| adv.cmp(x, y))?;
+        assert!(adv.count < 20 * n, "quadratic behaviour of {strategy:?}");
+        println!(
+            "{YL}{strategy:?}{UN} comparisons per item, random: {GR}{:.3}{UN} adversarial: {GR}{:.3}{UN}",
+            count as f64 / n as f64,
+            adv.count as f64 / n as f64
+        );
+    }
+    Ok(())
+}
+
+/// Musser's (1997) median-of-3 killer sequence of even length `n`, which makes the pivots
+/// from the first, middle and last positions the second smallest items of their ranges
+fn m3killer(n: usize) -> Vec<usize> {
+    let k = n / 2;
+    let mut v = vec![0_usize; n];
+    for i in 1..=k {
+        v[i - 1] = if (i & 1) == 1 { i } else { k + i - 1 };
+        v[k + i - 1] = 2 * i;
+    }
+    v
+}
+
+#[test]
+fn killers() -> Result<(), Me> {
+    use SelectStrategy::*;
+    for n in [1000_usize, 10000, 100000] {
+        let v = m3killer(n);
+        for strategy in [MidOf3, SeededSamples(1), SeededSamples(0x5eed)] {
+            let mut count = 0_usize;
+            let Medians::Even((&m1, &m2)) = v.median_by_with(strategy, &mut |a, b| {
+                count += 1;
+                a.cmp(b)
+            })?
+            else {
+                panic!("even medians expected")
+            };
+            assert_eq!((m1, m2), (n / 2, n / 2 + 1));
+            assert!(
+                count < 20 * n,
+                "quadratic behaviour of {strategy:?} on the killer sequence"
+            );
+            // the same seed repeats the same run
+            let mut recount = 0_usize;
+            v.median_by_with(strategy, &mut |a, b| {
+                recount += 1;
+                a.cmp(b)
+            })?;
+            assert_eq!(count, recount);
+            // adaptive adversary
+            let ids: Vec<usize> = (0..n).collect();
+            let mut adv = Adversary::new(n);
+            ids.median_by_with(strategy, &mut |&x, &y| adv.cmp(x, y))?;
+            assert!(
+                adv.count < 20 * n,
+                "quadratic behaviour of {strategy:?} against the adversary"
+            );
+            println!(
+                "{YL}{strategy:?}{UN} {n} comparisons per item, killer: {GR}{:.3}{UN} adversarial: {GR}{:.3}{UN}",
+                count as f64 / n as f64,
+                adv.count as f64 / n as f64
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn bunched() -> Result<(), Me> {
+    // timestamps like data, all sharing their top bits
+    let base = 0x0123_4567_0000_0000_u64;
+    for d in [3_usize, 4, 10, 1000, 1001, 100000] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        for mask in [0xffff_u64, 0xff, 1, 0] {
+            let data: Vec<u64> = v.iter().map(|&x| base + (x & mask)).collect();
+            let mut sorted = data.clone();
+            sorted.sort_unstable();
+            let mut s = data.clone();
+            match medianu64(&mut s)? {
+                Medians::Odd(&m) => assert_eq!(m, sorted[d / 2]),
+                Medians::Even((&m1, &m2)) => {
+                    assert_eq!((m1, m2), (sorted[d / 2 - 1], sorted[d / 2]))
+                }
+            };
+            let ks = [0, d / 4, d / 2, d - 1];
+            let res = select_manyu64(&mut s, &ks)?;
+            for (i, &k) in ks.iter().enumerate() {
+                assert_eq!(res[i], sorted[k]);
+            }
+        }
+    }
+    let mut s: Vec<u64> = ranv_u64(11)
+        .expect("Random vec generation failed")
+        .iter()
+        .map(|&x| base + (x & 0xff))
+        .collect();
+    println!("Bunched data: {}", s.gr());
+    println!("{}", medianu64(&mut s)?);
+    Ok(())
+}
+
+#[test]
+fn radix() -> Result<(), Me> {
+    for d in [1_usize, 2, 3, 15, 16, 17, 100, 1000, 1001, 100000, 100001] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        for mask in [u64::MAX, 0xffff_0000_0000_ffff, 0x1ff, 1, 0] {
+            let data: Vec<u64> = v
+                .iter()
+                .map(|&x| 0x0123_4567_0000_0000 | (x & mask))
+                .collect();
+            let mut s = data.clone();
+            let expected = match medianu64(&mut s)? {
+                Medians::Odd(&m) => ConstMedians::Odd(m),
+                Medians::Even((&m1, &m2)) => ConstMedians::Even((m1.min(m2), m1.max(m2))),
+            };
+            assert_eq!(medu64_bytes(&data)?, expected);
+            // the deprecated medu64 still returns the plain pair
+            #[allow(deprecated)]
+            let old = medu64(&mut data.clone())?;
+            match expected {
+                ConstMedians::Odd(m) => assert_eq!(old, (m, m)),
+                ConstMedians::Even(pair) => assert_eq!(old, pair),
+            };
+        }
+    }
+    let v = ranv_u64(10).expect("Random vec generation failed");
+    println!("Data: {}\nmedu64_bytes {}", v.gr(), medu64_bytes(&v)?);
+    Ok(())
+}
+
+#[test]
+fn radixkeys() -> Result<(), Me> {
+    /// checks `median_radix` against sorting
+    fn check<T: RadixKey + Ord + core::fmt::Debug>(data: &[T]) -> Result<(), Me> {
+        let mut sorted = data.to_vec();
+        sorted.sort_unstable();
+        let n = data.len();
+        let expected = if (n & 1) == 1 {
+            ConstMedians::Odd(sorted[n / 2])
+        } else {
+            ConstMedians::Even((sorted[n / 2 - 1], sorted[n / 2]))
+        };
+        assert_eq!(median_radix(data)?, expected);
+        Ok(())
+    }
+    for d in [1_usize, 2, 3, 4, 101, 1000, 10001] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        check(&v.iter().map(|&x| x as u8).collect::<Vec<u8>>())?;
+        check(&v.iter().map(|&x| x as i8).collect::<Vec<i8>>())?;
+        check(&v.iter().map(|&x| x as u16).collect::<Vec<u16>>())?;
+        check(&v.iter().map(|&x| x as i16).collect::<Vec<i16>>())?;
+        check(&v.iter().map(|&x| x as u32).collect::<Vec<u32>>())?;
+        check(&v.iter().map(|&x| (x as i32) % 100).collect::<Vec<i32>>())?;
+        check(&v)?;
+        check(&v.iter().map(|&x| x as i64).collect::<Vec<i64>>())?;
+        check(
+            &v.iter()
+                .map(|&x| (x as u128) << 64 | x as u128)
+                .collect::<Vec<u128>>(),
+        )?;
+        check(
+            &v.iter()
+                .map(|&x| (x as i128) - (1 << 100))
+                .collect::<Vec<i128>>(),
+        )?;
+        check(&v.iter().map(|&x| x as usize).collect::<Vec<usize>>())?;
+        check(&v.iter().map(|&x| x as isize).collect::<Vec<isize>>())?;
+    }
+    assert_eq!(i64::from_key((-3_i64).to_key()), -3);
+    assert!(median_radix::<i32>(&[]).is_err());
+    let v = [i32::MIN, -7, 0, 7, i32::MAX];
+    println!("Data: {}\nmedian_radix {}", v.gr(), median_radix(&v)?);
+    Ok(())
+}
+
+#[test]
+fn radixfloats() -> Result<(), Me> {
+    let specials = [
+        f64::NEG_INFINITY,
+        -f64::MAX,
+        -1.5,
+        -f64::MIN_POSITIVE,
+        -0.0,
+        0.0,
+        f64::MIN_POSITIVE,
+        2.5,
+        f64::MAX,
+        f64::INFINITY,
+    ];
+    for w in specials.windows(2) {
+        assert!(w[0].to_key() < w[1].to_key());
+        assert_eq!(f64::from_key(w[0].to_key()).to_bits(), w[0].to_bits());
+        assert!((w[0] as f32).to_key() <= (w[1] as f32).to_key());
+    }
+    // keys order NaNs as total_cmp does
+    assert!((-f64::NAN).to_key() < f64::NEG_INFINITY.to_key());
+    assert!(f64::NAN.to_key() > f64::INFINITY.to_key());
+    for d in [1_usize, 2, 3, 4, 101, 1000, 10000, 10001] {
+        let v = ranv_f64(d).expect("Random vec generation failed");
+        let w: Vec<f64> = v.iter().map(|&x| 1e6 * (x - 0.5)).collect();
+        let b: Vec<f64> = v.iter().map(|&x| 1000.0 + (x * 4.0).floor()).collect();
+        for data in [v, w, b] {
+            assert_eq!(data.medf_radix()?, data.medf_checked()?);
+        }
+    }
+    let mut v = ranv_f64(11).expect("Random vec generation failed");
+    println!("Data: {}\nmedf_radix: {}", v.gr(), v.medf_radix()?.gr());
+    v[5] = f64::NAN;
+    assert!(matches!(v.medf_radix(), Err(MedError::Nan(_))));
+    assert!(matches!(v.medf_checked(), Err(MedError::Nan(_))));
+    // also for one and two items
+    for short in [&[f64::NAN][..], &[1., f64::NAN]] {
+        assert!(matches!(short.medf_radix(), Err(MedError::Nan(_))));
+        assert!(matches!(short.medf_checked(), Err(MedError::Nan(_))));
+    }
+    Ok(())
+}
+
+#[test]
+fn medf32() -> Result<(), Me> {
+    for d in [1_usize, 2, 3, 4, 101, 1000, 10000, 10001] {
+        let v32: Vec<f32> = ranv_f64(d)
+            .expect("Random vec generation failed")
+            .iter()
+            .map(|&x| (1e3 * (x - 0.5)) as f32)
+            .collect();
+        let v64: Vec<f64> = v32.iter().map(|&x| x as f64).collect();
+        let m = v32.medf_checked()?;
+        assert_eq!(m, v64.medf_checked()? as f32);
+        assert_eq!(v32.medf_radix()?, m);
+        assert_eq!(v32.medf_unchecked(), m);
+        assert_eq!(v32.select_kf(d / 3)?, v64.select_kf(d / 3)? as f32);
+        assert_eq!(
+            v32.quantile(0.3, QuantileMethod::Linear)?,
+            v64.quantile(0.3, QuantileMethod::Linear)? as f32
+        );
+        // absolute differences are rounded differently in f32 and in f64
+        assert!((v32.madf(m) - v64.madf(m as f64) as f32).abs() < 1e-3);
+    }
+    let v = [
+        9_f32, 10., 18., 17., 16., 15., 14., 1., 2., 3., 4., 5., 6., 7., 8.,
+    ];
+    let w = [
+        1_f32, 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15.,
+    ];
+    let median = v.medf_checked()?;
+    println!("f32 data: {}", v.gr());
+    println!("Median±mad: {GR}{}±{}{UN}", median, v.madf(median));
+    println!("Weighted median: {GR}{}{UN}", v.medf_weighted(&w, 0.0001)?);
+    println!(
+        "Correlation with weights: {GR}{}{UN}",
+        v.medf_correlation(&w)?
+    );
+    assert_eq!(v.medf_zeroed(median).medf_checked()?, 0.);
+    let nanv = [1_f32, f32::NAN, 3.];
+    assert!(matches!(nanv.medf_checked(), Err(MedError::Nan(_))));
+    assert!(matches!(nanv.medf_radix(), Err(MedError::Nan(_))));
+    // also for one and two items
+    for short in [&[f32::NAN][..], &[1., f32::NAN]] {
+        assert!(matches!(short.medf_radix(), Err(MedError::Nan(_))));
+        assert!(matches!(short.medf_checked(), Err(MedError::Nan(_))));
+    }
+    Ok(())
+}
+
+#[test]
+fn inplace() -> Result<(), Me> {
+    for data in u64_fixtures() {
+        let d = data.len();
+        let mut sorted = data.clone();
+        sorted.sort_unstable();
+        let expected = data.qmedian_by(&mut <u64>::cmp, |&x| x as f64)?;
+        let mut s = data.clone();
+        assert_eq!(
+            s.qmedian_in_place_by(&mut <u64>::cmp, |&x| x as f64)?,
+            expected
+        );
+        let mut s = data.clone();
+        let lowmed = match s.median_in_place_by(&mut <u64>::cmp)? {
+            Medians::Odd(&m) => m,
+            Medians::Even((&m1, &m2)) => {
+                assert_eq!(m2, sorted[d / 2]);
+                m1
+            }
+        };
+        let need = (d - 1) / 2;
+        assert_eq!(lowmed, sorted[need]);
+        // the data is partitioned around the median and remains a permutation
+        assert!(s[..need].iter().all(|&x| x <= lowmed));
+        assert!(s[need..].iter().all(|&x| x >= lowmed));
+        s.sort_unstable();
+        assert_eq!(s, sorted);
+    }
+    let mut empty: [u64; 0] = [];
+    assert!(empty.median_in_place_by(&mut <u64>::cmp).is_err());
+    Ok(())
+}
+
+#[test]
+fn argmedians() -> Result<(), Me> {
+    for data in u64_fixtures() {
+        let d = data.len();
+        // indices sorted by (value, index) define the tie breaking
+        let mut order: Vec<usize> = (0..d).collect();
+        order.sort_unstable_by_key(|&i| (data[i], i));
+        let expected = if (d & 1) == 1 {
+            ConstMedians::Odd(order[d / 2])
+        } else {
+            ConstMedians::Even((order[d / 2 - 1], order[d / 2]))
+        };
+        let vf: Vec<f64> = data.iter().map(|&x| x as f64).collect();
+        let vf32: Vec<f32> = data.iter().map(|&x| (x >> 40) as f32).collect();
+        assert_eq!(data.argmedian_by(&mut <u64>::cmp)?, expected);
+        assert_eq!(argmedianu64(&data)?, expected);
+        assert_eq!(vf.argmedf()?, expected);
+        assert_eq!(vf32.argmedf()?, vf32.argmedian_by(&mut <f32>::total_cmp)?);
+        for k in (0..d).step_by(1 + d / 50) {
+            assert_eq!(data.argselect_k_by(k, &mut <u64>::cmp)?, order[k]);
+        }
+    }
+    // ties at the median are broken by the lowest indices
+    let ties = [5_u64, 3, 5, 1, 5, 9];
+    assert_eq!(
+        ties.argmedian_by(&mut <u64>::cmp)?,
+        ConstMedians::Even((0, 2))
+    );
+    assert_eq!(argmedianu64(&ties)?, ConstMedians::Even((0, 2)));
+    assert_eq!(argmedianu64(&ties[1..])?, ConstMedians::Odd(1));
+    assert_eq!(ties.argselect_k_by(4, &mut <u64>::cmp)?, 4);
+    assert!(argmedianu64(&[]).is_err());
+    assert!([1_f64, f64::NAN].argmedf().is_err());
+    Ok(())
+}
+
+#[test]
+fn iterators() -> Result<(), Me> {
+    for v in u64_fixtures() {
+        let expected = medu64_bytes(&v)?;
+        assert_eq!(v.iter().copied().iter_medianu64()?, expected);
+        assert_eq!(v.iter().copied().iter_median_by(&mut <u64>::cmp)?, expected);
+        let vf: Vec<f64> = v.iter().map(|&x| x as f64).collect();
+        assert_eq!(vf.iter().copied().iter_medf()?, vf.medf_checked()?);
+        // owned items that are not Copy
+        let strings: Vec<String> = v.iter().map(|x| format!("{x:020}")).collect();
+        let m = strings
+            .clone()
+            .iter_median_by(&mut |a: &String, b: &String| a.cmp(b))?;
+        let fromstr = |s: &String| s.parse::<u64>().expect("iterators: parse failed");
+        match (m, expected) {
+            (ConstMedians::Odd(s), ConstMedians::Odd(e)) => assert_eq!(fromstr(&s), e),
+            (ConstMedians::Even((s1, s2)), ConstMedians::Even((e1, e2))) => {
+                assert_eq!((fromstr(&s1), fromstr(&s2)), (e1, e2))
+            }
+            _ => panic!("iterators: parity mismatch"),
+        };
+    }
+    // filter chain
+    let evens = (1..=100_u64).filter(|x| x % 2 == 0);
+    assert_eq!(evens.iter_medianu64()?, ConstMedians::Even((50, 52)));
+    // two items in descending order still give ascending medians
+    assert_eq!(medu64_bytes(&[9, 1])?, ConstMedians::Even((1, 9)));
+    assert_eq!(
+        medianu64(&mut [9, 1])?.to_string(),
+        medu64_bytes(&[1, 9])?.to_string()
+    );
+    assert_eq!(
+        [9_u64, 1].median_by(&mut <u64>::cmp)?.to_string(),
+        medu64_bytes(&[1, 9])?.to_string()
+    );
+    assert!(std::iter::empty::<u64>().iter_medianu64().is_err());
+    assert!(std::iter::empty::<f64>().iter_medf().is_err());
+    assert!([1_f64, f64::NAN].into_iter().iter_medf().is_err());
+    Ok(())
+}
+
+#[test]
+fn axes() -> Result<(), Me> {
+    let (rows, cols) = (101, 6);
+    let data = ranv_f64(rows * cols).expect("Random vec generation failed");
+    let colmeds = medf_axis(&data, &[rows, cols], 0)?;
+    let rowmeds = medf_axis(&data, &[rows, cols], 1)?;
+    assert_eq!((colmeds.len(), rowmeds.len()), (cols, rows));
+    for (c, &m) in colmeds.iter().enumerate() {
+        let column: Vec<f64> = (0..rows).map(|r| data[r * cols + c]).collect();
+        assert_eq!(m, column.medf_checked()?);
+        let view = Strided::new(&data, c, cols, rows)?;
+        assert_eq!(view.medf_checked()?, m);
+        assert_eq!(view.madf(m), column.madf(m));
+    }
+    for (r, &m) in rowmeds.iter().enumerate() {
+        assert_eq!(m, data[r * cols..(r + 1) * cols].medf_checked()?);
+    }
+    let colmads = madf_axis(&data, &[rows, cols], 0, &colmeds)?;
+    let qmeds = qmedian_axis_by(&data, &[rows, cols], 0, &mut <f64>::total_cmp, |&x| x)?;
+    assert_eq!(qmeds, colmeds);
+    println!(
+        "Column medians: {}\nColumn mads: {}",
+        colmeds.gr(),
+        colmads.gr()
+    );
+    // three dimensional, the middle axis
+    let cube: Vec<u64> = (0..24).collect();
+    let meds = qmedian_axis_by(&cube, &[2, 3, 4], 1, &mut <u64>::cmp, |&x| x as f64)?;
+    assert_eq!(meds, [4., 5., 6., 7., 16., 17., 18., 19.]);
+    assert!(medf_axis(&data, &[rows, cols + 1], 0).is_err());
+    assert!(medf_axis(&data, &[rows, cols], 2).is_err());
+    assert!(Strided::new(&data, 1, cols, rows + 1).is_err());
+    // ragged stride, which does not divide the data length
+    let ragged: Vec<u64> = (0..10).rev().collect();
+    let view = Strided::new(&ragged, 1, 4, 3)?;
+    assert_eq!(view.iter().copied().collect::<Vec<u64>>(), [8, 4, 0]);
+    assert_eq!(view.qmedian_by(&mut <u64>::cmp, |&x| x as f64)?, 4.);
+    assert!(Strided::new(&ragged, 1, 4, 4).is_err());
+    // overflowing views and shapes are size errors, not panics
+    assert!(Strided::new(&data, 1, usize::MAX, 2).is_err());
+    assert!(Strided::new(&data, usize::MAX, 1, 2).is_err());
+    assert!(Strided::new(&data, 0, usize::MAX, 1).is_ok());
+    assert!(medf_axis(&data, &[usize::MAX, 2, 2], 0).is_err());
+    assert!(
+        qmedian_axis_by(&cube, &[2, usize::MAX, 2], 1, &mut <u64>::cmp, |&x| x
+            as f64)
+        .is_err()
+    );
+    Ok(())
+}
+
+#[test]
+fn parallel() -> Result<(), Me> {
+    for d in [PAR_THRESHOLD + 1, PAR_THRESHOLD + 2] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        // bunched data with many repeated values
+        let w: Vec<u64> = v.iter().map(|&x| x % 7).collect();
+        for data in [v, w] {
+            let expected = medu64_bytes(&data)?;
+            assert_eq!(par_medianu64(&mut data.clone())?, expected);
+            let vf: Vec<f64> = data.iter().map(|&x| x as f64 - 1e18).collect();
+            assert_eq!(par_medf(&vf)?, vf.medf_checked()?);
+            let seqmed = data.median_by(&mut <u64>::cmp)?.to_string();
+            assert_eq!(par_median_by(&data, <u64>::cmp)?.to_string(), seqmed);
+        }
+    }
+    // medians near f64::MAX must not overflow to infinity
+    for d in [PAR_THRESHOLD + 1, PAR_THRESHOLD + 2] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        let vf: Vec<f64> = v
+            .iter()
+            .map(|&x| f64::MAX * (0.75 + (x % 1000) as f64 / 4000.))
+            .collect();
+        let med = par_medf(&vf)?;
+        assert!(med.is_finite());
+        if (d & 1) == 1 {
+            assert_eq!(med, vf.medf_checked()?);
+        } else {
+            let mut sorted = vf.clone();
+            sorted.sort_unstable_by(f64::total_cmp);
+            assert_eq!(med, sorted[d / 2 - 1] / 2. + sorted[d / 2] / 2.);
+        };
+    }
+    let mut vf = vec![1_f64; PAR_THRESHOLD];
+    vf[7] = f64::NAN;
+    assert!(par_medf(&vf).is_err());
+    Ok(())
+}
+
+#[test]
+fn blockpart() -> Result<(), Me> {
+    for d in [1_usize, 2, 63, 64, 128, 129, 130, 500, 1000, 10001] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        for modulus in [2_u64, 3, 1000] {
+            let mut s = v.clone();
+            let pred = |&x: &u64| x % modulus == 0;
+            let front = block_part(&mut s, 0..d, pred);
+            assert_eq!(front, v.iter().filter(|&x| pred(x)).count());
+            assert!(s[..front].iter().all(pred) && !s[front..].iter().any(pred));
+            // the data remains a permutation
+            let (mut sorted, mut vsorted) = (s.clone(), v.clone());
+            sorted.sort_unstable();
+            vsorted.sort_unstable();
+            assert_eq!(sorted, vsorted);
+        }
+        // primitive selection by the block partitioning
+        let vf: Vec<f64> = v.iter().map(|&x| (x % 1000) as f64).collect();
+        let mut sorted = vf.clone();
+        sorted.sort_unstable_by(f64::total_cmp);
+        assert_eq!(vf.select_kf(d / 3)?, sorted[d / 3]);
+        assert_eq!(
+            vf.medf_unchecked(),
+            (sorted[(d - 1) / 2] + sorted[d / 2]) / 2.
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn stats() -> Result<(), Me> {
+    for d in [2_usize, 3, 16, 17, 1000, 10001] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        let mut sorted = v.clone();
+        sorted.sort_unstable();
+        let med = if (d & 1) == 1 {
+            ConstMedians::Odd(sorted[d / 2])
+        } else {
+            ConstMedians::Even((sorted[d / 2 - 1], sorted[d / 2]))
+        };
+        // comparator calls counted independently of the stats
+        let mut calls = 0_usize;
+        let (m, st) = v.median_by_stats(&mut |a: &u64, b: &u64| {
+            calls += 1;
+            a.cmp(b)
+        })?;
+        assert_eq!(m.to_string(), v.median_by(&mut <u64>::cmp)?.to_string());
+        assert_eq!(st.comparisons, calls);
+        assert_eq!(st.passes, st.remaining.len());
+        // the ranges in play shrink with each pass
+        assert!(st.remaining.windows(2).all(|w| w[1] < w[0]));
+        // up to NETWORK_MAX items go through the median network, without any passes
+        assert_eq!(st.passes == 0, d <= NETWORK_MAX);
+        println!("median_by  {d:5}: {st:?}");
+        let mut s = v.clone();
+        let (m, st) = medianu64_stats(&mut s)?;
+        assert_eq!(m.to_string(), medianu64(&mut v.clone())?.to_string());
+        assert_eq!(st.passes, st.remaining.len());
+        let (m, st) = medu64_stats(&v)?;
+        assert_eq!(m, med);
+        assert!(st.passes >= 1 && st.remaining.last() == Some(&0));
+        println!("medu64     {d:5}: {st:?}");
+        // sorted data is already partitioned by every bit, so nothing is swapped
+        let (_, st) = medianu64_stats(&mut sorted.clone())?;
+        assert_eq!(st.swaps, 0);
+    }
+    Ok(())
+}
+
+#[test]
+fn verify() -> Result<(), Me> {
+    let v = [5_u64, 9, 3, 7, 1, 8, 2, 7];
+    // sorted: 1 2 3 5 7 7 8 9
+    let counts = rank_range_of(&v, &7, &mut <u64>::cmp);
+    assert_eq!(
+        counts,
+        RankRange {
+            less: 4,
+            equal: 2,
+            greater: 2
+        }
+    );
+    assert_eq!(counts.ranks(), 4..6);
+    let check = is_median_by(&v, &5, &mut <u64>::cmp)?;
+    assert!(check.is_valid() && check.offset() == 0);
+    assert!(is_median_by(&v, &7, &mut <u64>::cmp)?.is_valid());
+    let check = is_median_by(&v, &2, &mut <u64>::cmp)?;
+    assert!(!check.is_valid());
+    assert_eq!(check.offset(), -2);
+    assert_eq!(is_median_by(&v, &9, &mut <u64>::cmp)?.offset(), 3);
+    // an item that does not occur in the data is never valid
+    assert!(!is_kth_by(&v, 3, &4, &mut <u64>::cmp)?.is_valid());
+    assert!(is_kth_by(&v, 5, &7, &mut <u64>::cmp)?.is_valid());
+    assert!(is_kth_by(&v, 8, &7, &mut <u64>::cmp).is_err());
+    assert!(is_median_by(&[] as &[u64], &7, &mut <u64>::cmp).is_err());
+    // checks of the generic medians, by comparator only
+    let w = ranv_f64(1001).expect("Random vec generation failed");
+    let Medians::Odd(m) = w.median_by(&mut <f64>::total_cmp)? else {
+        return merror("other", "odd median expected");
+    };
+    assert!(is_median_by(&w, m, &mut <f64>::total_cmp)?.is_valid());
+    Ok(())
+}
+
+#[test]
+fn topk() -> Result<(), Me> {
+    for d in [1_usize, 2, 3, 10, 101, 1000, 10000] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        let w: Vec<u64> = v.iter().map(|&x| x % 7).collect();
+        for data in [v, w] {
+            let mut sorted = data.clone();
+            sorted.sort_unstable();
+            for k in [0, 1, d / 3, d] {
+                let small: Vec<u64> = data
+                    .smallest_k_by(k, &mut <u64>::cmp)?
+                    .into_iter()
+                    .copied()
+                    .collect();
+                assert_eq!(small, sorted[..k]);
+                let large: Vec<u64> = data
+                    .largest_k_by(k, &mut <u64>::cmp)?
+                    .into_iter()
+                    .copied()
+                    .collect();
+                assert!(large.iter().eq(sorted[d - k..].iter().rev()));
+            }
+            for rng in [0..d, d / 4..d / 2, d / 2..d / 2 + 1, d..d] {
+                let window: Vec<u64> = data
+                    .partial_sort_by(rng.clone(), &mut <u64>::cmp)?
+                    .into_iter()
+                    .copied()
+                    .collect();
+                assert_eq!(window, sorted[rng]);
+            }
+            assert!(data.smallest_k_by(d + 1, &mut <u64>::cmp).is_err());
+            // best_k within a subrange only
+            let half = &data[d / 2..];
+            let mut hsorted = half.to_vec();
+            hsorted.sort_unstable();
+            assert_eq!(*best_k(&data, 1, d / 2..d, |a, b| a.cmp(b)), hsorted[0]);
+            assert_eq!(
+                *best_k(&data, half.len(), d / 2..d, |a, b| a.cmp(b)),
+                hsorted[half.len() - 1]
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn splits() -> Result<(), Me> {
+    for d in [1_usize, 2, 3, 10, 101, 1000, 10000] {
+        let v = ranv_u64(d).expect("Random vec generation failed");
+        let w: Vec<u64> = v.iter().map(|&x| x % 5).collect();
+        for data in [v, w] {
+            let mut sorted = data.clone();
+            sorted.sort_unstable();
+            for k in [0, d / 2, d - 1] {
+                let mut s = data.clone();
+                let p = s.partition_at_k_by(k, &mut <u64>::cmp)?;
+                let m = *p.item();
+                assert_eq!(m, sorted[k]);
+                assert!(p.less.iter().all(|&x| x < m));
+                assert!(p.equal.iter().all(|&x| x == m));
+                assert!(p.greater.iter().all(|&x| x > m));
+                assert_eq!(p.less.len(), sorted.partition_point(|&x| x < m));
+                assert_eq!(p.greater.len(), d - sorted.partition_point(|&x| x <= m));
+            }
+            let mut s = data.clone();
+            let p = s.partition_at_median_by(&mut <u64>::cmp)?;
+            assert_eq!(*p.item(), sorted[(d - 1) / 2]);
+            let mut s = data.clone();
+            assert!(s.partition_at_k_by(d, &mut <u64>::cmp).is_err());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn workspace() -> Result<(), Me> {
+    let mut ws = MedianWorkspace::with_capacity(1000);
+    // windows of varying lengths, all served by the same buffers
+    for d in [1_usize, 2, 3, 17, 1000, 999, 10] {
+        let v = ranv_f64(d).expect("Random vec generation failed");
+        let m = ws.medf_checked(&v)?;
+        assert_eq!(m, v.medf_checked()?);
+        assert_eq!(ws.medf_unchecked(&v), v.medf_unchecked());
+        assert_eq!(ws.madf(&v, m), v.madf(m));
+        assert_eq!(ws.mad(&v, m, |&x| x), v.mad(m, |&x| x));
+        assert_eq!(ws.qmedian_by(&v, &mut <f64>::total_cmp, |&x| x)?, m);
+        assert_eq!(
+            ws.median_by(&v, &mut <f64>::total_cmp)?.to_string(),
+            v.median_by(&mut <f64>::total_cmp)?.to_string()
+        );
+        let u = ranv_u64(d).expect("Random vec generation failed");
+        assert_eq!(ws.uqmedian(&u, |&x| x)?, u.uqmedian(|&x| x)?);
+    }
+    assert!(ws.medf_checked(&[]).is_err());
+    assert!(ws.medf_checked(&[1.0, f64::NAN]).is_err());
+    Ok(())
+}
+
+/// Checks `median_of_array::<N>` on all the 0-1 inputs,
+/// which by the 0-1 principle proves its network correct
+fn network_exhaustive<const N: usize>() {
+    for bits in 0..1_u32 << N {
+        let a: [u64; N] = core::array::from_fn(|i| u64::from((bits >> i) & 1));
+        // the number of ones gives the sorted order directly
+        let zeros = N - bits.count_ones() as usize;
+        let m1 = u64::from((N - 1) / 2 >= zeros);
+        let m2 = u64::from(N / 2 >= zeros);
+        let expected = if (N & 1) == 1 {
+            ConstMedians::Odd(m1)
+        } else {
+            ConstMedians::Even((m1, m2))
+        };
+        assert_eq!(median_of_array(a, &mut <u64>::cmp), expected, "{N} items");
+    }
+}
+
+/// Checks `median_of_array::<N>` and the fast paths served by its network on random data
+/// and, for up to 16 items, exhaustively, see `network_exhaustive`
+fn network_check<const N: usize>() -> Result<(), Me> {
+    let expected = |sorted: &[u64]| {
+        if (N & 1) == 1 {
+            ConstMedians::Odd(sorted[N / 2])
+        } else {
+            ConstMedians::Even((sorted[N / 2 - 1], sorted[N / 2]))
+        }
+    };
+    if N <= 16 {
+        network_exhaustive::<N>();
+    };
+    for _ in 0..100 {
+        let v = ranv_u64(N).expect("Random vec generation failed");
+        let a: [u64; N] = v.clone().try_into().expect("length N");
+        let mut sorted = v.clone();
+        sorted.sort_unstable();
+        assert_eq!(
+            median_of_array(a, &mut <u64>::cmp),
+            expected(&sorted),
+            "{N} items"
+        );
+        // the fast paths served by the networks
+        assert_eq!(
+            v.median_by(&mut <u64>::cmp)?.to_string(),
+            expected(&sorted).to_string()
+        );
+        let vf: Vec<f64> = v.iter().map(|&x| x as f64).collect();
+        assert_eq!(vf.medf_checked()?, f64::from(expected(&sorted)));
+        let vf32: Vec<f32> = v.iter().map(|&x| (x % 1000) as f32).collect();
+        let mut sorted32 = vf32.clone();
+        sorted32.sort_unstable_by(f32::total_cmp);
+        assert_eq!(
+            vf32.medf_unchecked(),
+            (sorted32[(N - 1) / 2] + sorted32[N / 2]) / 2.
+        );
+        let v8: Vec<u8> = v.iter().map(|&x| x as u8).collect();
+        let mut sorted8 = v8.clone();
+        sorted8.sort_unstable();
+        assert_eq!(medianu8(&v8)?, (sorted8[(N - 1) / 2], sorted8[N / 2]));
+    }
+    Ok(())
+}
+
+#[test]
+fn networks() -> Result<(), Me> {
+    macro_rules! check_sizes {
+        ($($n:literal)*) => { $(network_check::<$n>()?;)* };
+    }
+    check_sizes!(1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25);
+    Ok(())
+}
+
+/// The exhaustive 0-1 check of the networks for 17 to 25 items, 2^N inputs each,
+/// takes minutes in debug builds: `cargo test --release -- --ignored`
+#[test]
+#[ignore]
+fn networks_exhaustive() {
+    macro_rules! check_sizes {
+        ($($n:literal)*) => { $(network_exhaustive::<$n>();)* };
+    }
+    check_sizes!(17 18 19 20 21 22 23 24 25);
+}